@@ -1,44 +1,414 @@
-use std::fmt::Debug;
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::any::Any;
+use core::cmp::Reverse;
+use core::fmt::Debug;
+use core::panic::Location;
+
+use hashbrown::HashMap;
+
+#[cfg(feature = "async")]
+use tokio::sync::Semaphore;
+
 use crate::types::*;
 
+/// Stand-in for the semaphore limiting a listener when async listeners are
+/// compiled out; never constructed.
+#[cfg(not(feature = "async"))]
+pub enum Semaphore {}
+
 #[derive(Clone)]
 pub enum Callback {
     Sync(SyncCallback),
+    #[cfg(feature = "async")]
     Async(AsyncCallback),
+    TrySync(TrySyncCallback),
+    #[cfg(feature = "async")]
+    TryAsync(TryAsyncCallback),
+    SyncPattern(SyncPatternCallback),
+    #[cfg(feature = "async")]
+    AsyncPattern(AsyncPatternCallback),
+}
+
+impl Callback {
+    pub fn kind(&self) -> ListenerKind {
+        match self {
+            Callback::Sync(_) | Callback::TrySync(_) | Callback::SyncPattern(_) => {
+                ListenerKind::Sync
+            }
+            #[cfg(feature = "async")]
+            Callback::Async(_) | Callback::TryAsync(_) | Callback::AsyncPattern(_) => {
+                ListenerKind::Async
+            }
+        }
+    }
 }
 
+#[cfg(feature = "std")]
 #[derive(Clone)]
 pub enum ThreadSafeCallback {
     Sync(SyncThreadSafeCallback),
+    #[cfg(feature = "async")]
     Async(AsyncThreadSafeCallback),
+    TrySync(TrySyncThreadSafeCallback),
+    #[cfg(feature = "async")]
+    TryAsync(TryAsyncThreadSafeCallback),
+    SyncPattern(SyncPatternThreadSafeCallback),
+    #[cfg(feature = "async")]
+    AsyncPattern(AsyncPatternThreadSafeCallback),
 }
 
 pub struct Handler {
     pub id: HandlerId,
     pub callback: Callback,
     pub once: bool,
+    pub priority: i32,
+    pub prepend: bool,
+    pub label: Option<String>,
+    /// Bounds concurrent async invocations of this listener.
+    pub semaphore: Option<Arc<Semaphore>>,
+    pub filter: Option<Filter>,
+    /// Pattern a pattern listener was registered under, so it can be removed
+    /// without searching the trie.
+    pub pattern: Option<String>,
+}
+
+impl Handler {
+    pub fn new(id: HandlerId, callback: Callback, options: ListenerOptions) -> Self {
+        Self {
+            id,
+            callback,
+            once: options.once,
+            priority: options.priority,
+            prepend: options.prepend,
+            label: options.label,
+            semaphore: listener_semaphore(options.max_concurrency),
+            filter: None,
+            pattern: None,
+        }
+    }
+
+    pub fn info(&self) -> ListenerInfo {
+        ListenerInfo {
+            id: self.id,
+            once: self.once,
+            kind: self.callback.kind(),
+            priority: self.priority,
+            label: self.label.clone(),
+        }
+    }
 }
 
 impl Debug for Handler {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Handler")
             .field("id", &self.id)
             .field("once", &self.once)
+            .field("priority", &self.priority)
+            .field("label", &self.label)
             .finish()
     }
 }
 
+#[cfg(feature = "std")]
+impl ThreadSafeCallback {
+    pub fn kind(&self) -> ListenerKind {
+        match self {
+            ThreadSafeCallback::Sync(_)
+            | ThreadSafeCallback::TrySync(_)
+            | ThreadSafeCallback::SyncPattern(_) => ListenerKind::Sync,
+            #[cfg(feature = "async")]
+            ThreadSafeCallback::Async(_)
+            | ThreadSafeCallback::TryAsync(_)
+            | ThreadSafeCallback::AsyncPattern(_) => ListenerKind::Async,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 pub struct ThreadSafeHandler {
     pub id: HandlerId,
     pub callback: ThreadSafeCallback,
     pub once: bool,
+    pub priority: i32,
+    pub prepend: bool,
+    pub label: Option<String>,
+    /// Bounds concurrent async invocations of this listener.
+    pub semaphore: Option<Arc<Semaphore>>,
+    pub filter: Option<ThreadSafeFilter>,
+    /// Pattern a pattern listener was registered under, so it can be removed
+    /// without searching the trie.
+    pub pattern: Option<String>,
 }
 
+#[cfg(feature = "std")]
+impl ThreadSafeHandler {
+    pub fn new(id: HandlerId, callback: ThreadSafeCallback, options: ListenerOptions) -> Self {
+        Self {
+            id,
+            callback,
+            once: options.once,
+            priority: options.priority,
+            prepend: options.prepend,
+            label: options.label,
+            semaphore: listener_semaphore(options.max_concurrency),
+            filter: None,
+            pattern: None,
+        }
+    }
+
+    pub fn info(&self) -> ListenerInfo {
+        ListenerInfo {
+            id: self.id,
+            once: self.once,
+            kind: self.callback.kind(),
+            priority: self.priority,
+            label: self.label.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 impl Debug for ThreadSafeHandler {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("ThreadSafeHandler")
             .field("id", &self.id)
             .field("once", &self.once)
+            .field("priority", &self.priority)
+            .field("label", &self.label)
             .finish()
     }
 }
+
+/// Semaphore for `ListenerOptions::max_concurrency`.
+#[cfg(feature = "async")]
+fn listener_semaphore(max_concurrency: Option<usize>) -> Option<Arc<Semaphore>> {
+    max_concurrency.and_then(crate::tasks::semaphore)
+}
+
+#[cfg(not(feature = "async"))]
+fn listener_semaphore(_max_concurrency: Option<usize>) -> Option<Arc<Semaphore>> {
+    None
+}
+
+#[cfg(feature = "std")]
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// `key` in the borrowed form the emitter methods take.
+pub fn key_ref<K: EventKey>(key: &K) -> &K::Ref {
+    key.borrow()
+}
+
+/// Index at which a handler with the given options keeps the list ordered
+/// by descending priority.
+pub fn insert_position<I>(mut priorities: I, options: &ListenerOptions) -> usize
+where
+    I: ExactSizeIterator<Item = i32>,
+{
+    let len = priorities.len();
+    priorities
+        .position(|priority| {
+            if options.prepend {
+                priority <= options.priority
+            } else {
+                priority < options.priority
+            }
+        })
+        .unwrap_or(len)
+}
+
+/// Sort key that merges the listeners of several patterns: by priority, then
+/// in registration order, except that `prepend`ed listeners go first, the
+/// latest ahead. Agrees with `insert_position` within a single pattern.
+pub fn dispatch_order(priority: i32, prepend: bool, id: HandlerId) -> (Reverse<i32>, i128) {
+    let seq = if prepend { -i128::from(id) } else { i128::from(id) };
+    (Reverse(priority), seq)
+}
+
+/// Max-listener settings, shared by every clone of an emitter.
+#[derive(Clone)]
+pub struct ListenerLimits<K, H> {
+    pub default_limit: Option<usize>,
+    pub per_event: HashMap<K, usize>,
+    pub strict: bool,
+    pub hook: Option<H>,
+}
+
+impl<K, H> Default for ListenerLimits<K, H> {
+    fn default() -> Self {
+        Self {
+            default_limit: None,
+            per_event: HashMap::new(),
+            strict: false,
+            hook: None,
+        }
+    }
+}
+
+impl<K: EventKey, H> ListenerLimits<K, H> {
+    /// Checks adding one listener to `count` existing ones of `event`. `Err`
+    /// rejects the registration (strict mode); `Ok(Some(_))` asks the caller
+    /// to warn once the listener is in place.
+    #[track_caller]
+    pub fn check(
+        &self,
+        event: &K::Ref,
+        count: usize,
+    ) -> Result<Option<MaxListenersExceeded>, MaxListenersExceeded> {
+        let limit = self.per_event.get(event).copied();
+        self.check_limit(limit, count, || K::name(event).into_owned())
+    }
+
+    /// Like `check`, for a listener of `pattern`. A per-event limit applies
+    /// when its key's name is the pattern.
+    #[track_caller]
+    pub fn check_pattern(
+        &self,
+        pattern: &str,
+        count: usize,
+    ) -> Result<Option<MaxListenersExceeded>, MaxListenersExceeded> {
+        let limit = self
+            .per_event
+            .iter()
+            .find(|(event, _)| K::name(key_ref(*event)) == pattern)
+            .map(|(_, limit)| *limit);
+        self.check_limit(limit, count, || pattern.to_string())
+    }
+
+    /// The key is only formatted once a limit is actually exceeded.
+    #[track_caller]
+    fn check_limit<F>(
+        &self,
+        limit: Option<usize>,
+        count: usize,
+        name: F,
+    ) -> Result<Option<MaxListenersExceeded>, MaxListenersExceeded>
+    where
+        F: FnOnce() -> String,
+    {
+        let Some(limit) = limit.or(self.default_limit) else {
+            return Ok(None);
+        };
+        let count = count + 1;
+        if limit == 0 || count <= limit {
+            return Ok(None);
+        }
+
+        let exceeded = MaxListenersExceeded {
+            event: name(),
+            count,
+            limit,
+            location: Location::caller(),
+        };
+        if self.strict {
+            return Err(exceeded);
+        }
+
+        // Like Node, warn only once when the limit is first crossed.
+        Ok((count == limit + 1).then_some(exceeded))
+    }
+}
+
+#[track_caller]
+pub fn registered(result: Result<HandlerId, MaxListenersExceeded>) -> HandlerId {
+    match result {
+        Ok(id) => id,
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// When a payload was retained. Only `Retention::Window` reads it, and that
+/// policy needs a clock, so without std there is nothing to record.
+#[cfg(feature = "std")]
+type Timestamp = std::time::Instant;
+#[cfg(not(feature = "std"))]
+type Timestamp = ();
+
+#[cfg(feature = "std")]
+fn now() -> Timestamp {
+    Timestamp::now()
+}
+
+#[cfg(not(feature = "std"))]
+fn now() -> Timestamp {}
+
+/// Payloads kept for one event under its retention policy.
+pub struct Retained<A> {
+    policy: Retention,
+    payloads: VecDeque<(Timestamp, A)>,
+}
+
+impl<A: Clone> Retained<A> {
+    pub fn new(policy: Retention) -> Self {
+        Self {
+            policy,
+            payloads: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, args: A) {
+        self.payloads.push_back((now(), args));
+        self.trim();
+    }
+
+    /// Payloads still retained, oldest first.
+    pub fn payloads(&mut self) -> Vec<A> {
+        self.trim();
+        self.payloads.iter().map(|(_, args)| args.clone()).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.payloads.clear();
+    }
+
+    fn trim(&mut self) {
+        let keep = match self.policy {
+            Retention::Last => 1,
+            Retention::LastN(n) => n,
+            #[cfg(feature = "std")]
+            Retention::Window(window) => {
+                while let Some((at, _)) = self.payloads.front()
+                    && at.elapsed() > window
+                {
+                    self.payloads.pop_front();
+                }
+                return;
+            }
+        };
+        while self.payloads.len() > keep {
+            self.payloads.pop_front();
+        }
+    }
+}
+
+/// Set once `shutdown` starts; later emits are rejected and counted.
+#[derive(Default)]
+pub struct ShutdownState {
+    pub closing: bool,
+    pub rejected: usize,
+}
+
+impl ShutdownState {
+    /// Whether an emit must be dropped because the emitter is shutting down.
+    pub fn reject(&mut self) -> bool {
+        if self.closing {
+            self.rejected += 1;
+        }
+        self.closing
+    }
+}
+
+/// Nothing is ever spawned when async listeners are compiled out.
+#[cfg(not(feature = "async"))]
+pub enum Spawned {}
@@ -1,5 +1,17 @@
 pub mod single_thread;
+#[cfg(feature = "std")]
 pub mod multi_thread;
+pub mod subscription;
+#[cfg(feature = "async")]
+pub mod stream;
+#[cfg(feature = "async")]
+mod next;
+#[cfg(feature = "async")]
+mod rpc;
 
 pub use single_thread::*;
-pub use multi_thread::*;
\ No newline at end of file
+#[cfg(feature = "std")]
+pub use multi_thread::*;
+pub use subscription::*;
+#[cfg(feature = "async")]
+pub use stream::*;
@@ -1,210 +1,1698 @@
+use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::marker::PhantomData;
+#[cfg(feature = "async")]
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "async")]
 use std::pin::Pin;
 use std::sync::Arc;
-use std::sync::Mutex;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock};
+#[cfg(feature = "async")]
+use std::time::Duration;
 
+#[cfg(feature = "tokio")]
+use tokio::runtime::Handle;
+#[cfg(feature = "async")]
+use tokio::sync::{Semaphore, oneshot};
+
+#[cfg(feature = "async")]
+use super::rpc::{Responder, Responders};
 use crate::basis::*;
+#[cfg(feature = "tokio")]
+use crate::runtime::TokioSpawner;
+#[cfg(feature = "async")]
+use crate::runtime::{self, Spawner};
+#[cfg(feature = "async")]
+use crate::tasks::*;
+use crate::topic::TopicTrie;
+use crate::ts_args;
 use crate::types::*;
 
-pub trait ThreadSafeEventEmitter: Send + Sync {
-    fn on<F>(&self, event: &str, callback: F) -> HandlerId
+pub trait ThreadSafeEventEmitter<K: EventKey = String>: Send + Sync {
+    fn on<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static;
+
+    fn once<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static;
+
+    fn on_with<F>(&self, event: &K::Ref, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static;
+
+    /// Registers a listener that is only called for emits whose args satisfy
+    /// `predicate`.
+    #[track_caller]
+    fn on_filtered<P, F>(&self, event: &K::Ref, predicate: P, callback: F) -> HandlerId
+    where
+        P: Fn(&ThreadSafeArgs) -> bool + Send + Sync + 'static,
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        self.on_filtered_with(event, ListenerOptions::new(), predicate, callback)
+    }
+
+    /// Filtered once listener: it is removed after the first emit that matches
+    /// `predicate`, not after the first emit.
+    #[track_caller]
+    fn once_filtered<P, F>(&self, event: &K::Ref, predicate: P, callback: F) -> HandlerId
+    where
+        P: Fn(&ThreadSafeArgs) -> bool + Send + Sync + 'static,
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        self.on_filtered_with(event, ListenerOptions::new().once(), predicate, callback)
+    }
+
+    fn on_filtered_with<P, F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        predicate: P,
+        callback: F,
+    ) -> HandlerId
     where
+        P: Fn(&ThreadSafeArgs) -> bool + Send + Sync + 'static,
         F: Fn(ThreadSafeArgs) + Send + Sync + 'static;
 
-    fn once<F>(&self, event: &str, callback: F) -> HandlerId
+    /// Like `on_with`, but returns the error instead of panicking when strict
+    /// max-listeners mode rejects the registration.
+    fn add_listener<F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        callback: F,
+    ) -> Result<HandlerId, MaxListenersExceeded>
     where
         F: Fn(ThreadSafeArgs) + Send + Sync + 'static;
 
-    fn off(&self, event: &str, id: HandlerId) -> bool;
+    #[track_caller]
+    fn prepend_listener<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        self.on_with(event, ListenerOptions::new().prepend(), callback)
+    }
+
+    #[track_caller]
+    fn prepend_once_listener<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        self.on_with(event, ListenerOptions::new().once().prepend(), callback)
+    }
+
+    /// Subscribes to every event matching `pattern`, e.g. `"order.*"` or
+    /// `"sensor.#"`. The callback receives the concrete event name.
+    #[track_caller]
+    fn on_pattern<F>(&self, pattern: &str, callback: F) -> HandlerId
+    where
+        F: Fn(&str, ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        self.on_pattern_with(pattern, ListenerOptions::new(), callback)
+    }
+
+    fn on_pattern_with<F>(&self, pattern: &str, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(&str, ThreadSafeArgs) + Send + Sync + 'static;
+
+    /// Registers a fallible listener. An `Err` is emitted on the `"error"` event,
+    /// or panics out of `emit` when nothing listens to `"error"`.
+    fn try_on<F, E>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Result<(), E> + Send + Sync + 'static,
+        E: Into<ThreadSafeHandlerError>;
+
+    fn off(&self, event: &K::Ref, id: HandlerId) -> bool;
+
+    fn off_all(&self, event: &K::Ref);
+
+    fn emit(&self, event: &K::Ref, args: Arc<Vec<ThreadSafeArg>>);
 
-    fn off_all(&self, event: &str);
+    /// Number of listeners an emit of `event` would call, pattern listeners included.
+    fn listener_count(&self, event: &K::Ref) -> usize {
+        self.listeners(event).len()
+    }
+
+    /// Events that currently have listeners, in no particular order.
+    fn event_names(&self) -> Vec<K>;
+
+    /// Patterns that currently have listeners, written with `*` and `#`.
+    fn event_patterns(&self) -> Vec<String>;
+
+    /// Listeners an emit of `event` would call, in dispatch order.
+    fn listeners(&self, event: &K::Ref) -> Vec<ListenerInfo>;
 
-    fn emit(&self, event: &str, args: Arc<Vec<ThreadSafeArg>>);
+    /// Listeners registered under exactly `event`, which may be a pattern.
+    fn raw_listeners(&self, event: &K::Ref) -> Vec<ListenerInfo>;
 }
 
-pub trait ThreadSafeAsyncEventEmitter {
-    fn on_async<F>(&self, event: &str, callback: F) -> HandlerId
+#[cfg(feature = "async")]
+pub trait ThreadSafeAsyncEventEmitter<K: EventKey = String> {
+    fn on_async<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static;
+
+    fn once_async<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static;
+
+    fn on_async_with<F>(&self, event: &K::Ref, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static;
+
+    /// Async counterpart of `on_filtered`.
+    #[track_caller]
+    fn on_filtered_async<P, F>(&self, event: &K::Ref, predicate: P, callback: F) -> HandlerId
+    where
+        P: Fn(&ThreadSafeArgs) -> bool + Send + Sync + 'static,
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_filtered_async_with(event, ListenerOptions::new(), predicate, callback)
+    }
+
+    /// Async counterpart of `once_filtered`.
+    #[track_caller]
+    fn once_filtered_async<P, F>(&self, event: &K::Ref, predicate: P, callback: F) -> HandlerId
+    where
+        P: Fn(&ThreadSafeArgs) -> bool + Send + Sync + 'static,
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_filtered_async_with(event, ListenerOptions::new().once(), predicate, callback)
+    }
+
+    fn on_filtered_async_with<P, F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        predicate: P,
+        callback: F,
+    ) -> HandlerId
+    where
+        P: Fn(&ThreadSafeArgs) -> bool + Send + Sync + 'static,
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static;
+
+    /// Like `on_async_with`, but returns the error instead of panicking when
+    /// strict max-listeners mode rejects the registration.
+    fn add_async_listener<F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        callback: F,
+    ) -> Result<HandlerId, MaxListenersExceeded>
     where
         F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
             + Send
             + Sync
             + 'static;
 
-    fn once_async<F>(&self, event: &str, callback: F) -> HandlerId
+    #[track_caller]
+    fn prepend_async_listener<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_async_with(event, ListenerOptions::new().prepend(), callback)
+    }
+
+    #[track_caller]
+    fn prepend_once_async_listener<F>(&self, event: &K::Ref, callback: F) -> HandlerId
     where
         F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_async_with(event, ListenerOptions::new().once().prepend(), callback)
+    }
+
+    #[track_caller]
+    fn on_pattern_async<F>(&self, pattern: &str, callback: F) -> HandlerId
+    where
+        F: Fn(&str, ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_pattern_async_with(pattern, ListenerOptions::new(), callback)
+    }
+
+    fn on_pattern_async_with<F>(
+        &self,
+        pattern: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> HandlerId
+    where
+        F: Fn(&str, ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static;
+
+    /// Async counterpart of `try_on`. An unhandled error panics the spawned task.
+    fn try_on_async<F, E>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = Result<(), E>> + Send>>
+            + Send
+            + Sync
+            + 'static,
+        E: Into<ThreadSafeHandlerError> + 'static;
+
+    /// Like `on_async`, but each call also gets the listener's cancellation
+    /// token, which is cancelled when the listener is removed by `off` / `off_all`.
+    fn on_async_cancellable<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs, CancellationToken) -> Pin<Box<dyn Future<Output = ()> + Send>>
             + Send
             + Sync
             + 'static;
+
+    /// Emits like `emit`, then waits until every async handler has finished.
+    fn emit_and_wait(
+        &self,
+        event: &K::Ref,
+        args: Arc<Vec<ThreadSafeArg>>,
+    ) -> impl Future<Output = EmitSummary> + Send;
+
+    /// Emits like `emit`, then waits until every async handler has got past
+    /// its concurrency limits, so a producer slows down to the pace of the
+    /// handlers instead of queueing work without bound.
+    fn emit_async(
+        &self,
+        event: &K::Ref,
+        args: Arc<Vec<ThreadSafeArg>>,
+    ) -> impl Future<Output = ()> + Send;
 }
 
 #[derive(Clone)]
-pub struct MultiThreadEventEmitter {
-    listeners: Arc<Mutex<HashMap<String, Vec<ThreadSafeHandler>>>>,
+pub struct MultiThreadEventEmitter<K: EventKey = String> {
+    listeners: Arc<Mutex<HashMap<K, Vec<ThreadSafeHandler>>>>,
+    patterns: Arc<Mutex<TopicTrie<ThreadSafeHandler>>>,
     id_counter: Arc<AtomicU64>,
-    handle: Option<tokio::runtime::Handle>,
+    retained: Arc<Mutex<HashMap<K, Retained<ThreadSafeArgs>>>>,
+    #[cfg(feature = "async")]
+    lanes: Arc<Mutex<HashMap<K, oneshot::Receiver<()>>>>,
+    #[cfg(feature = "async")]
+    in_flight: Arc<Mutex<InFlight<K>>>,
+    #[cfg(feature = "async")]
+    tracker: TaskCounter,
+    #[cfg(feature = "async")]
+    responders: Arc<Mutex<Responders<K>>>,
+    shutdown: Arc<Mutex<ShutdownState>>,
+    #[cfg(feature = "async")]
+    runtime: Arc<Mutex<RuntimeState<K>>>,
+    config: Arc<RwLock<Arc<Config<K>>>>,
+}
+
+/// Settings made through the builder-style setters, shared by every clone.
+#[derive(Clone)]
+struct Config<K: EventKey> {
+    #[cfg(feature = "async")]
+    missing_runtime: MissingRuntime,
+    panic_hook: Option<ThreadSafePanicHook>,
+    limits: ListenerLimits<K, ThreadSafeMaxListenersHook>,
+    #[cfg(feature = "async")]
+    dispatch_modes: DispatchModes<K>,
+    #[cfg(feature = "async")]
+    cancel_policy: CancelPolicy,
+    #[cfg(feature = "async")]
+    concurrency: ConcurrencyLimits<K>,
+    interceptors: Vec<Arc<dyn ThreadSafeInterceptor<K>>>,
+    meta_events: bool,
+    delimiter: char,
+}
+
+/// Builds a [`MultiThreadEventEmitter`] together with the executor its async
+/// listeners are spawned on.
+pub struct MultiThreadEventEmitterBuilder<K = String> {
+    #[cfg(feature = "async")]
+    spawner: Option<Arc<dyn Spawner>>,
+    #[cfg(feature = "async")]
+    missing_runtime: MissingRuntime,
+    key: PhantomData<fn() -> K>,
+}
+
+impl<K> Default for MultiThreadEventEmitterBuilder<K> {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "async")]
+            spawner: None,
+            #[cfg(feature = "async")]
+            missing_runtime: MissingRuntime::default(),
+            key: PhantomData,
+        }
+    }
+}
+
+impl<K: EventKey<Ref: Sync> + Send + Sync> MultiThreadEventEmitterBuilder<K> {
+    /// Builder for an emitter keyed by `K`; `MultiThreadEventEmitter::builder`
+    /// for one keyed by event names.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawns async listeners with `spawner`. Without one, the runtime current
+    /// at emit time is used.
+    #[cfg(feature = "async")]
+    pub fn spawner<S>(mut self, spawner: S) -> Self
+    where
+        S: Spawner,
+    {
+        self.spawner = Some(Arc::new(spawner));
+        self
+    }
+
+    /// Spawns async listeners on the tokio runtime behind `handle`.
+    #[cfg(feature = "tokio")]
+    pub fn handle(self, handle: Handle) -> Self {
+        self.spawner(TokioSpawner(handle))
+    }
+
+    /// What happens to async listeners when there is no runtime at all;
+    /// `MissingRuntime::Error` by default.
+    #[cfg(feature = "async")]
+    pub fn missing_runtime(mut self, policy: MissingRuntime) -> Self {
+        self.missing_runtime = policy;
+        self
+    }
+
+    #[cfg(feature = "async")]
+    pub fn build(self) -> MultiThreadEventEmitter<K> {
+        let emitter = MultiThreadEventEmitter::default()
+            .configure(|config| config.missing_runtime = self.missing_runtime);
+        emitter.lock_runtime().spawner = self.spawner;
+        emitter
+    }
+
+    #[cfg(not(feature = "async"))]
+    pub fn build(self) -> MultiThreadEventEmitter<K> {
+        MultiThreadEventEmitter::default()
+    }
 }
 
 impl MultiThreadEventEmitter {
+    pub fn builder() -> MultiThreadEventEmitterBuilder {
+        MultiThreadEventEmitterBuilder::new()
+    }
+
+    /// Emitter keyed by event names. For another [`EventKey`] use
+    /// `MultiThreadEventEmitter::<K>::default()`.
     pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K: EventKey<Ref: Sync> + Send + Sync> Default for MultiThreadEventEmitter<K> {
+    fn default() -> Self {
+        let config = Config {
+            #[cfg(feature = "async")]
+            missing_runtime: MissingRuntime::default(),
+            panic_hook: None,
+            limits: ListenerLimits::default(),
+            #[cfg(feature = "async")]
+            dispatch_modes: DispatchModes::default(),
+            #[cfg(feature = "async")]
+            cancel_policy: CancelPolicy::default(),
+            #[cfg(feature = "async")]
+            concurrency: ConcurrencyLimits::default(),
+            interceptors: Vec::new(),
+            meta_events: false,
+            delimiter: '.',
+        };
         Self {
             listeners: Arc::new(Mutex::new(HashMap::new())),
+            patterns: Arc::new(Mutex::new(TopicTrie::new())),
             id_counter: Arc::new(AtomicU64::new(0)),
-            handle: None,
+            retained: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "async")]
+            lanes: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "async")]
+            in_flight: Arc::new(Mutex::new(InFlight::default())),
+            #[cfg(feature = "async")]
+            tracker: TaskCounter::default(),
+            #[cfg(feature = "async")]
+            responders: Arc::new(Mutex::new(HashMap::new())),
+            shutdown: Arc::new(Mutex::new(ShutdownState::default())),
+            #[cfg(feature = "async")]
+            runtime: Arc::new(Mutex::new(RuntimeState::default())),
+            config: Arc::new(RwLock::new(Arc::new(config))),
         }
     }
+}
 
-    pub fn set_handle(mut self, handle: tokio::runtime::Handle) -> Self {
-        self.handle = Some(handle);
+impl<K: EventKey<Ref: Sync> + Send + Sync> MultiThreadEventEmitter<K> {
+    #[deprecated(note = "use `MultiThreadEventEmitter::builder().handle(..)` or `attach_handle`")]
+    #[cfg(feature = "tokio")]
+    pub fn set_handle(self, handle: Handle) -> Self {
+        self.attach_handle(handle);
         self
     }
 
-    fn get_id(&self) -> HandlerId {
-        self.id_counter.fetch_add(1, Ordering::SeqCst)
+    /// Spawns async listeners with `spawner` from now on, along with any
+    /// handlers queued under `MissingRuntime::Queue`. Applies to every clone.
+    #[cfg(feature = "async")]
+    pub fn attach_spawner<S>(&self, spawner: S)
+    where
+        S: Spawner,
+    {
+        let spawner: Arc<dyn Spawner> = Arc::new(spawner);
+        let queued = {
+            let mut runtime = self.lock_runtime();
+            runtime.spawner = Some(spawner.clone());
+            mem::take(&mut runtime.queued)
+        };
+
+        for QueuedHandler { event, id, semaphore, fut } in queued {
+            let event = key_ref(&event);
+            let mut lane = self.reserve_lane(event);
+            self.spawn_handler(&*spawner, event, lane.as_mut(), id, semaphore, fut);
+        }
+    }
+
+    /// Spawns async listeners on the tokio runtime behind `handle`; see
+    /// [`attach_spawner`](Self::attach_spawner).
+    #[cfg(feature = "tokio")]
+    pub fn attach_handle(&self, handle: Handle) {
+        self.attach_spawner(TokioSpawner(handle));
+    }
+
+    /// Warns once an event has more than `n` listeners; `0` means unlimited.
+    pub fn set_max_listeners(self, n: usize) -> Self {
+        self.configure(|config| config.limits.default_limit = Some(n))
+    }
+
+    /// Overrides the max-listeners limit for a single event or pattern.
+    pub fn set_event_max_listeners(self, event: &K::Ref, n: usize) -> Self {
+        self.configure(|config| {
+            config.limits.per_event.insert(event.to_owned(), n);
+        })
     }
-}
 
-impl ThreadSafeEventEmitter for MultiThreadEventEmitter {
-    fn on<F>(&self, event: &str, callback: F) -> HandlerId
+    /// Called instead of printing to stderr when a limit is exceeded.
+    pub fn set_max_listeners_hook<F>(self, hook: F) -> Self
     where
-        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+        F: Fn(&MaxListenersExceeded) + Send + Sync + 'static,
     {
-        let id = self.get_id();
-        let handler = ThreadSafeHandler {
-            id,
-            callback: ThreadSafeCallback::Sync(Arc::new(callback)),
-            once: false,
-        };
+        self.configure(|config| config.limits.hook = Some(Arc::new(hook)))
+    }
 
-        let mut listeners = self.listeners.lock().unwrap();
-        listeners
-            .entry(event.to_string())
-            .or_default()
-            .push(handler);
+    /// In strict mode a registration over the limit fails: `add_listener`
+    /// returns the error and the other registration methods panic with it.
+    pub fn set_strict_max_listeners(self, strict: bool) -> Self {
+        self.configure(|config| config.limits.strict = strict)
+    }
 
-        id
+    /// How async handlers run; `DispatchMode::Concurrent` by default.
+    #[cfg(feature = "async")]
+    pub fn set_dispatch_mode(self, mode: DispatchMode) -> Self {
+        self.configure(|config| config.dispatch_modes.default_mode = mode)
+    }
+
+    /// Overrides the dispatch mode for a single event.
+    #[cfg(feature = "async")]
+    pub fn set_event_dispatch_mode(self, event: &K::Ref, mode: DispatchMode) -> Self {
+        self.configure(|config| {
+            config
+                .dispatch_modes
+                .per_event
+                .insert(event.to_owned(), mode);
+        })
+    }
+
+    /// Caps how many async handlers of this emitter run at once; further
+    /// handlers wait in a queue until a slot frees up. 0 means unlimited.
+    #[cfg(feature = "async")]
+    pub fn set_max_concurrency(self, n: usize) -> Self {
+        self.configure(|config| config.concurrency.set_emitter(n))
+    }
+
+    /// Caps how many async handlers of `event` run at once. 0 means unlimited.
+    #[cfg(feature = "async")]
+    pub fn set_event_max_concurrency(self, event: &K::Ref, n: usize) -> Self {
+        self.configure(|config| config.concurrency.set_event(event, n))
+    }
+
+    /// What `off` / `off_all` do with async tasks the removed listener still
+    /// has running; `CancelPolicy::Detach` by default.
+    #[cfg(feature = "async")]
+    pub fn set_cancel_policy(self, policy: CancelPolicy) -> Self {
+        self.configure(|config| config.cancel_policy = policy)
     }
 
-    fn once<F>(&self, event: &str, callback: F) -> HandlerId
+    /// Appends an interceptor to the emit pipeline; see [`ThreadSafeInterceptor`].
+    pub fn with_interceptor<I>(self, interceptor: I) -> Self
     where
-        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+        I: ThreadSafeInterceptor<K> + 'static,
     {
-        let id = self.get_id();
-        let handler = ThreadSafeHandler {
-            id,
-            callback: ThreadSafeCallback::Sync(Arc::new(callback)),
-            once: true,
+        self.configure(|config| config.interceptors.push(Arc::new(interceptor)))
+    }
+
+    /// Emits `"newListener"` / `"removeListener"` whenever a listener is added
+    /// or removed, including once listeners removed after firing.
+    pub fn set_meta_events(self, enabled: bool) -> Self {
+        self.configure(|config| config.meta_events = enabled)
+    }
+
+    /// Keeps payloads of `event` according to `policy` and replays them to
+    /// every listener later added for `event`, before any live emit.
+    pub fn set_retention(self, event: &K::Ref, policy: Retention) -> Self {
+        self.lock_retained().insert(event.to_owned(), Retained::new(policy));
+        self
+    }
+
+    /// Forgets the payloads retained for `event`; its policy stays in place.
+    pub fn clear_retained(&self, event: &K::Ref) {
+        if let Some(retained) = self.lock_retained().get_mut(event) {
+            retained.clear();
+        }
+    }
+
+    /// Segment separator used by pattern subscriptions, `'.'` by default.
+    pub fn set_delimiter(self, delimiter: char) -> Self {
+        self.configure(|config| config.delimiter = delimiter)
+    }
+
+    /// Enables panic isolation: a panicking listener is reported to `hook`
+    /// and the remaining listeners still run.
+    pub fn set_panic_hook<F>(self, hook: F) -> Self
+    where
+        F: Fn(&ListenerPanic) + Send + Sync + 'static,
+    {
+        self.configure(|config| config.panic_hook = Some(Arc::new(hook)))
+    }
+
+    /// Applies a setting to the configuration shared by every clone.
+    fn configure<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut Config<K>),
+    {
+        {
+            let mut config = self.config.write().unwrap_or_else(PoisonError::into_inner);
+            f(Arc::make_mut(&mut config));
+        }
+        self
+    }
+
+    /// Snapshot of the configuration; no lock is held while it is used, so
+    /// listeners and hooks may reconfigure the emitter.
+    fn config(&self) -> Arc<Config<K>> {
+        self.config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Listeners are never called while the lock is held, so the map is
+    /// consistent even if another thread panicked with the guard alive.
+    fn lock_listeners(&self) -> MutexGuard<'_, HashMap<K, Vec<ThreadSafeHandler>>> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_retained(&self) -> MutexGuard<'_, HashMap<K, Retained<ThreadSafeArgs>>> {
+        self.retained.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[cfg(feature = "async")]
+    fn lock_lanes(&self) -> MutexGuard<'_, HashMap<K, oneshot::Receiver<()>>> {
+        self.lanes.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[cfg(feature = "async")]
+    fn lock_in_flight(&self) -> MutexGuard<'_, InFlight<K>> {
+        self.in_flight.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[cfg(feature = "async")]
+    fn lock_responders(&self) -> MutexGuard<'_, Responders<K>> {
+        self.responders.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_shutdown(&self) -> MutexGuard<'_, ShutdownState> {
+        self.shutdown.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[cfg(feature = "async")]
+    fn lock_runtime(&self) -> MutexGuard<'_, RuntimeState<K>> {
+        self.runtime.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_patterns(&self) -> MutexGuard<'_, TopicTrie<ThreadSafeHandler>> {
+        self.patterns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Stops the emitter: later emits are dropped, cancellation tokens are
+    /// cancelled, and in-flight async handlers get until `deadline` to finish
+    /// before the rest are aborted.
+    #[cfg(feature = "async")]
+    pub async fn shutdown(&self, deadline: Duration) -> ShutdownReport<K> {
+        self.lock_shutdown().closing = true;
+        self.lock_in_flight().cancel_tokens();
+
+        let sleep = self.sleep(deadline).unwrap_or_else(|| runtime::timer_sleep(deadline));
+        let drained = runtime::timeout(sleep, self.tracker.wait()).await;
+        let mut cancelled = match drained {
+            Some(()) => Vec::new(),
+            None => self.lock_in_flight().abort_all(),
         };
+        // Handlers still queued for a runtime will never run.
+        let queued = mem::take(&mut self.lock_runtime().queued);
+        cancelled.extend(queued.into_iter().map(|h| CancelledTask { event: h.event, id: h.id }));
+        ShutdownReport {
+            cancelled,
+            rejected: self.lock_shutdown().rejected,
+        }
+    }
 
-        let mut listeners = self.listeners.lock().unwrap();
-        listeners
-            .entry(event.to_string())
-            .or_default()
-            .push(handler);
+    /// Whether an emit must be dropped because `shutdown` was called.
+    fn reject_emit(&self) -> bool {
+        self.lock_shutdown().reject()
+    }
+
+    fn get_id(&self) -> HandlerId {
+        self.id_counter.fetch_add(1, Ordering::SeqCst)
+    }
+
+    #[track_caller]
+    fn add_handler(
+        &self,
+        event: &K::Ref,
+        callback: ThreadSafeCallback,
+        options: ListenerOptions,
+        filter: Option<ThreadSafeFilter>,
+    ) -> Result<HandlerId, MaxListenersExceeded> {
+        let (id, warning, payloads) = {
+            let mut listeners = self.lock_listeners();
+            let handlers = listeners.entry(event.to_owned()).or_default();
+            let warning = self.config().limits.check(event, handlers.len())?;
+            let id = self.get_id();
+            let index = insert_position(handlers.iter().map(|h| h.priority), &options);
+            let handler = ThreadSafeHandler {
+                filter,
+                ..ThreadSafeHandler::new(id, callback, options)
+            };
+            handlers.insert(index, handler);
+            // Taken under the listeners lock, so each payload reaches the new
+            // listener either here or from a concurrent emit, never both.
+            let payloads = self.lock_retained().get_mut(event).map(Retained::payloads);
+            (id, warning, payloads)
+        };
 
+        if let Some(warning) = warning {
+            self.warn_max_listeners(&warning);
+        }
+        if let Some(payloads) = payloads {
+            self.replay(event, id, payloads);
+        }
+        self.emit_meta(K::new_listener_event(), event, id);
+        Ok(id)
+    }
+
+    #[track_caller]
+    fn add_pattern_handler(
+        &self,
+        pattern: &str,
+        callback: ThreadSafeCallback,
+        options: ListenerOptions,
+    ) -> Result<HandlerId, MaxListenersExceeded> {
+        let (id, warning) = {
+            let mut patterns = self.lock_patterns();
+            let handlers = patterns.entry(pattern, self.config().delimiter);
+            let warning = self.config().limits.check_pattern(pattern, handlers.len())?;
+            let id = self.get_id();
+            let index = insert_position(handlers.iter().map(|h| h.priority), &options);
+            let handler = ThreadSafeHandler {
+                pattern: Some(pattern.to_string()),
+                ..ThreadSafeHandler::new(id, callback, options)
+            };
+            handlers.insert(index, handler);
+            (id, warning)
+        };
+
+        if let Some(warning) = warning {
+            self.warn_max_listeners(&warning);
+        }
+        self.emit_pattern_meta(K::new_listener_event(), pattern, id);
+        Ok(id)
+    }
+
+    // Called after the lock is released so the hook may register or remove listeners.
+    fn warn_max_listeners(&self, warning: &MaxListenersExceeded) {
+        match &self.config().limits.hook {
+            Some(hook) => hook(warning),
+            None => eprintln!("MaxListenersExceededWarning: {warning}"),
+        }
+    }
+
+    /// Registers a request responder. Responders are kept apart from
+    /// listeners, so emits, retention and introspection never see them.
+    #[cfg(feature = "async")]
+    pub(crate) fn add_responder(&self, event: &K::Ref, responder: Responder) -> HandlerId {
+        let id = self.get_id();
+        self.lock_responders()
+            .entry(event.to_owned())
+            .or_default()
+            .push((id, responder));
         id
     }
 
-    fn off(&self, event: &str, id: HandlerId) -> bool {
-        let mut listeners = self.listeners.lock().unwrap();
+    #[cfg(feature = "async")]
+    fn remove_responder(&self, event: &K::Ref, id: HandlerId) -> bool {
+        let mut responders = self.lock_responders();
+        let Some(handlers) = responders.get_mut(event) else {
+            return false;
+        };
+        let len = handlers.len();
+        handlers.retain(|(responder_id, _)| *responder_id != id);
+        len != handlers.len()
+    }
+
+    /// Spawns the future `start` builds from every responder of `event`, like
+    /// an async handler but without going through `emit`.
+    #[cfg(feature = "async")]
+    pub(crate) fn spawn_responders<F>(&self, event: &K::Ref, start: F)
+    where
+        F: Fn(&Responder) -> ThreadSafeHandlerFuture,
+    {
+        if self.reject_emit() {
+            return;
+        }
+        let responders = self.lock_responders().get(event).cloned().unwrap_or_default();
+        let mut spawner = None;
+        for (id, responder) in responders {
+            let fut = start(&responder);
+            match spawner.get_or_insert_with(|| self.runtime_spawner()) {
+                Some(spawner) => {
+                    self.spawn_handler(&**spawner, event, None, id, None, fut);
+                }
+                None => self.without_runtime(event, id, None, fut),
+            }
+        }
+    }
+
+    fn remove_handler(&self, event: &K::Ref, id: HandlerId) -> bool {
+        let mut listeners = self.lock_listeners();
 
         if let Some(handlers) = listeners.get_mut(event) {
             let len = handlers.len();
             handlers.retain(|h| h.id != id);
 
+            if len != handlers.len() {
+                return true;
+            }
+        }
+        drop(listeners);
+
+        let mut patterns = self.lock_patterns();
+        if !patterns.is_empty()
+            && let Some(handlers) = patterns.get_mut(&K::name(event), self.config().delimiter)
+        {
+            let len = handlers.len();
+            handlers.retain(|h| h.id != id);
+
             return len != handlers.len();
         }
 
         false
     }
 
-    fn off_all(&self, event: &str) {
-        self.listeners.lock().unwrap().remove(event);
+    /// Calls a newly added listener with the payloads retained for `event`.
+    fn replay(&self, event: &K::Ref, id: HandlerId, payloads: Vec<ThreadSafeArgs>) {
+        for args in payloads {
+            let handler = self
+                .lock_listeners()
+                .get(event)
+                .and_then(|handlers| handlers.iter().find(|h| h.id == id))
+                .map(|h| (h.callback.clone(), h.once, h.filter.clone(), h.semaphore.clone()));
+            let Some((callback, once, filter, semaphore)) = handler else {
+                return;
+            };
+            if filter.is_some_and(|filter| !filter(&args)) {
+                continue;
+            }
+
+            if once && !self.remove_handler(event, id) {
+                return;
+            }
+            let callbacks = vec![(id, callback, semaphore)];
+            self.invoke(event, args, callbacks, &mut EmitSummary::default());
+            if once {
+                self.emit_meta(K::remove_listener_event(), event, id);
+            }
+        }
+    }
+
+    fn emit_meta(&self, meta_event: Option<&K::Ref>, event: &K::Ref, id: HandlerId) {
+        if self.config().meta_events
+            && let Some(meta_event) = meta_event
+        {
+            let args = ts_args![event.to_owned(), id];
+            self.dispatch(meta_event, args, &mut EmitSummary::default());
+        }
     }
 
-    fn emit(&self, event: &str, args: Arc<Vec<ThreadSafeArg>>) {
-        let callbacks: Vec<ThreadSafeCallback> = self
-            .listeners
-            .lock()
-            .unwrap()
+    fn emit_pattern_meta(&self, meta_event: Option<&K::Ref>, pattern: &str, id: HandlerId) {
+        if self.config().meta_events
+            && let Some(meta_event) = meta_event
+        {
+            let args = ts_args![pattern.to_string(), id];
+            self.dispatch(meta_event, args, &mut EmitSummary::default());
+        }
+    }
+
+    /// Exact listeners followed by matching pattern listeners, ordered by
+    /// priority. Pattern listeners of equal priority run in registration order,
+    /// those registered with `prepend` first.
+    fn matching<T, F>(&self, event: &K::Ref, f: F) -> Vec<T>
+    where
+        F: Fn(&ThreadSafeHandler) -> T,
+    {
+        self.matching_in(&self.lock_listeners(), event, f)
+    }
+
+    /// `matching` for a caller that already holds the listeners lock.
+    fn matching_in<T, F>(
+        &self,
+        listeners: &HashMap<K, Vec<ThreadSafeHandler>>,
+        event: &K::Ref,
+        f: F,
+    ) -> Vec<T>
+    where
+        F: Fn(&ThreadSafeHandler) -> T,
+    {
+        let mut matched: Vec<(i32, T)> = listeners
             .get(event)
-            .map(|handlers| handlers.iter().map(|h| h.callback.clone()).collect())
+            .map(|handlers| handlers.iter().map(|h| (h.priority, f(h))).collect())
             .unwrap_or_default();
 
-        for callback in &callbacks {
-            if let ThreadSafeCallback::Sync(cb) = callback {
-                cb(args.clone());
+        let patterns = self.lock_patterns();
+        if patterns.is_empty() {
+            return matched.into_iter().map(|(_, item)| item).collect();
+        }
+        let mut pattern_handlers = patterns.matches(&K::name(event), self.config().delimiter);
+        pattern_handlers.sort_by_key(|h| dispatch_order(h.priority, h.prepend, h.id));
+        pattern_handlers.dedup_by_key(|h| h.id);
+
+        matched.extend(pattern_handlers.into_iter().map(|h| (h.priority, f(h))));
+        matched.sort_by_key(|(priority, _)| Reverse(*priority));
+
+        matched.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// The event errors of `event` are emitted on, if an emit of it would
+    /// reach any listener, pattern listeners included.
+    fn error_event(&self, event: &K::Ref) -> Option<&'static K::Ref> {
+        K::error_event().filter(|error_event| {
+            *error_event != event && !self.matching(error_event, |_| ()).is_empty()
+        })
+    }
+
+    /// Like `emit_error` with `NoRuntime`, but only warns when the error is
+    /// unhandled.
+    #[cfg(feature = "async")]
+    fn report_no_runtime(&self, event: &K::Ref, id: HandlerId) {
+        match self.error_event(event) {
+            Some(_) => self.emit_error(event, id, Box::new(NoRuntime)),
+            None => warn_no_runtime(&K::name(event), id),
+        }
+    }
+
+    fn emit_error(&self, event: &K::Ref, id: HandlerId, error: ThreadSafeHandlerError) {
+        let error = ListenerError {
+            event: K::name(event).into_owned(),
+            id,
+            error,
+        };
+
+        let Some(error_event) = self.error_event(event) else {
+            panic!("unhandled 'error' event: {error}");
+        };
+
+        let args = ts_args![error];
+        self.dispatch(error_event, args, &mut EmitSummary::default());
+    }
+
+    fn report_panic(
+        &self,
+        hook: &ThreadSafePanicHook,
+        event: &K::Ref,
+        id: HandlerId,
+        message: String,
+    ) {
+        hook(&ListenerPanic {
+            event: K::name(event).into_owned(),
+            id,
+            message,
+        });
+    }
+
+    /// Runs `f` inside every interceptor's `around_sync`, first one outermost.
+    fn around_sync<F>(
+        &self,
+        event: &K::Ref,
+        id: HandlerId,
+        f: F,
+    ) -> Result<(), ThreadSafeHandlerError>
+    where
+        F: FnOnce() -> Result<(), ThreadSafeHandlerError>,
+    {
+        fn chain<K: EventKey>(
+            interceptors: &[Arc<dyn ThreadSafeInterceptor<K>>],
+            event: &K::Ref,
+            id: HandlerId,
+            f: &mut dyn FnMut(),
+        ) {
+            match interceptors.split_first() {
+                Some((first, rest)) => {
+                    first.around_sync(event, id, &mut || chain(rest, event, id, f));
+                }
+                None => f(),
+            }
+        }
+
+        let config = self.config();
+        if config.interceptors.is_empty() {
+            return f();
+        }
+
+        let mut f = Some(f);
+        let mut result = Ok(());
+        chain(&config.interceptors, event, id, &mut || {
+            if let Some(f) = f.take() {
+                result = f();
+            }
+        });
+        result
+    }
+
+    #[cfg(feature = "async")]
+    fn around_async(
+        &self,
+        event: &K::Ref,
+        id: HandlerId,
+        fut: ThreadSafeHandlerFuture,
+    ) -> ThreadSafeHandlerFuture {
+        self.config()
+            .interceptors
+            .iter()
+            .rev()
+            .fold(fut, |fut, interceptor| interceptor.around_async(event, id, fut))
+    }
+
+    fn call_guarded<F>(
+        &self,
+        event: &K::Ref,
+        id: HandlerId,
+        f: F,
+    ) -> Result<(), ThreadSafeHandlerError>
+    where
+        F: FnOnce() -> Result<(), ThreadSafeHandlerError>,
+    {
+        let f = || self.around_sync(event, id, f);
+        let Some(hook) = self.config().panic_hook.clone() else {
+            return f();
+        };
+
+        panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+            self.report_panic(&hook, event, id, panic_message(&*payload));
+            Ok(())
+        })
+    }
+
+    /// In sequential mode, reserves the next place in `event`'s lane for one
+    /// emit. Reserved under a single lock so the handlers of concurrent emits
+    /// never interleave.
+    #[cfg(feature = "async")]
+    fn reserve_lane(&self, event: &K::Ref) -> Option<LaneSlot> {
+        match self.config().dispatch_modes.get(event) {
+            DispatchMode::Concurrent => None,
+            DispatchMode::Sequential => {
+                let (done, next) = oneshot::channel::<()>();
+                let previous = self.lock_lanes().insert(event.to_owned(), next);
+                Some(LaneSlot::new(previous, done))
+            }
+        }
+    }
+
+    /// Chains `fut` after the previous handler in `lane`, so handlers run in
+    /// dispatch order and emits in emit order. Then waits for a permit from
+    /// every concurrency limit that applies and signals the returned receiver
+    /// once `fut` is about to start.
+    #[cfg(feature = "async")]
+    fn schedule<F>(
+        &self,
+        event: &K::Ref,
+        lane: Option<&mut LaneSlot>,
+        semaphore: Option<Arc<Semaphore>>,
+        fut: F,
+    ) -> (impl Future<Output = ()> + Send + 'static, oneshot::Receiver<()>)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (previous, guard) = lane.map(LaneSlot::next).unzip();
+        let semaphores = self.config().concurrency.semaphores(event, semaphore);
+        let (started, on_started) = oneshot::channel::<()>();
+
+        let task = async move {
+            if let Some(previous) = previous.flatten() {
+                let _ = previous.await;
+            }
+            // Dropped when `fut` finishes or panics, releasing the next handler.
+            let _guard = guard;
+            let _permits = acquire_all(semaphores).await;
+            let _ = started.send(());
+            fut.await;
+        };
+        (task, on_started)
+    }
+
+    #[cfg(feature = "async")]
+    fn guard_task(
+        &self,
+        event: &K::Ref,
+        id: HandlerId,
+        fut: ThreadSafeHandlerFuture,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let fut = self.around_async(event, id, fut);
+        let emitter = self.clone();
+        let event = event.to_owned();
+        async move {
+            let Some(hook) = emitter.config().panic_hook.clone() else {
+                return fut.await;
+            };
+
+            if let Err(payload) = catch_unwind(fut).await {
+                emitter.report_panic(&hook, key_ref(&event), id, panic_message(&*payload));
+                // Keep unwinding so emit_and_wait still counts the panic.
+                panic::resume_unwind(payload);
+            }
+        }
+    }
+
+    /// Removes the once listeners in `once` that are still registered, each
+    /// paired with the pattern it was registered under, or `None` for
+    /// listeners of `event` itself. Only the emit that removes a once listener
+    /// may call it, so it fires at most once even under concurrent or
+    /// re-entrant emits.
+    fn claim_once(
+        &self,
+        event: &K::Ref,
+        once: Vec<(HandlerId, Option<String>)>,
+    ) -> Vec<(Option<String>, HandlerId)> {
+        let mut claimed = Vec::new();
+        if once.is_empty() {
+            return claimed;
+        }
+
+        let delimiter = self.config().delimiter;
+        let mut listeners = self.lock_listeners();
+        let mut patterns = self.lock_patterns();
+        for (id, pattern) in once {
+            let handlers = match &pattern {
+                Some(pattern) => patterns.get_mut(pattern, delimiter),
+                None => listeners.get_mut(event),
+            };
+            if let Some(handlers) = handlers
+                && let Some(index) = handlers.iter().position(|h| h.id == id)
+            {
+                handlers.remove(index);
+                claimed.push((pattern, id));
             }
         }
+        claimed
+    }
 
-        if let Some(handle) = &self.handle {
-            let handle = handle.clone();
-            for callback in callbacks {
-                if let ThreadSafeCallback::Async(cb) = callback {
-                    let args_clone = args.clone();
+    fn dispatch(
+        &self,
+        event: &K::Ref,
+        args: ThreadSafeArgs,
+        summary: &mut EmitSummary,
+    ) -> Vec<Spawned> {
+        let Some((event, args)) = self.before_emit(event, args) else {
+            return Vec::new();
+        };
+        let event = event.as_ref();
 
-                    handle.spawn(async move {
-                        cb(args_clone).await;
-                    });
+        // Retained and matched under one listeners lock, so a listener added
+        // concurrently gets this payload either replayed or live.
+        let matched = {
+            let listeners = self.lock_listeners();
+            if let Some(retained) = self.lock_retained().get_mut(event) {
+                retained.push(args.clone());
+            }
+            self.matching_in(&listeners, event, |h| {
+                let once = h.once.then(|| h.pattern.clone());
+                (h.id, once, h.callback.clone(), h.filter.clone(), h.semaphore.clone())
+            })
+        };
+        // A filtered once listener is only used up when its filter matched.
+        let mut once = Vec::new();
+        let matched: Vec<_> = matched
+            .into_iter()
+            .filter(|(id, pattern, _, filter, _)| {
+                let accepted = filter.as_ref().is_none_or(|filter| filter(&args));
+                if accepted && let Some(pattern) = pattern {
+                    once.push((*id, pattern.clone()));
                 }
+                accepted
+            })
+            .collect();
+        let removed = self.claim_once(event, once);
+        let callbacks = matched
+            .into_iter()
+            .filter(|(id, once, ..)| once.is_none() || removed.iter().any(|(_, c)| c == id))
+            .map(|(id, _, callback, _, semaphore)| (id, callback, semaphore))
+            .collect();
+        let tasks = self.invoke(event, args, callbacks, summary);
+
+        for (pattern, id) in removed {
+            match pattern {
+                Some(pattern) => self.emit_pattern_meta(K::remove_listener_event(), &pattern, id),
+                None => self.emit_meta(K::remove_listener_event(), event, id),
             }
         }
 
-        let mut listeners = self.listeners.lock().unwrap();
-        if let Some(handlers) = listeners.get_mut(event) {
-            handlers.retain(|h| !h.once);
+        tasks
+    }
+
+    /// Passes an emit through every interceptor; `None` if one dropped it.
+    fn before_emit<'a>(
+        &self,
+        event: &'a K::Ref,
+        args: ThreadSafeArgs,
+    ) -> Option<(Cow<'a, K::Ref>, ThreadSafeArgs)> {
+        let config = self.config();
+        if config.interceptors.is_empty() {
+            return Some((Cow::Borrowed(event), args));
+        }
+
+        let mut event = event.to_owned();
+        let mut args = args;
+        for interceptor in &config.interceptors {
+            if !interceptor.before_emit(&mut event, &mut args) {
+                return None;
+            }
+        }
+        Some((Cow::Owned(event), args))
+    }
+
+    /// Runs sync callbacks in order, then spawns async ones.
+    fn invoke(
+        &self,
+        event: &K::Ref,
+        args: ThreadSafeArgs,
+        callbacks: Vec<(HandlerId, ThreadSafeCallback, Option<Arc<Semaphore>>)>,
+        summary: &mut EmitSummary,
+    ) -> Vec<Spawned> {
+        for (id, callback, _) in &callbacks {
+            let result = match callback {
+                ThreadSafeCallback::Sync(cb) => self.call_guarded(event, *id, || {
+                    cb(args.clone());
+                    Ok(())
+                }),
+                ThreadSafeCallback::TrySync(cb) => {
+                    self.call_guarded(event, *id, || cb(args.clone()))
+                }
+                ThreadSafeCallback::SyncPattern(cb) => self.call_guarded(event, *id, || {
+                    cb(&K::name(event), args.clone());
+                    Ok(())
+                }),
+                #[cfg(feature = "async")]
+                ThreadSafeCallback::Async(_)
+                | ThreadSafeCallback::TryAsync(_)
+                | ThreadSafeCallback::AsyncPattern(_) => continue,
+            };
+            if let Err(error) = result {
+                self.emit_error(event, *id, error);
+            }
+            summary.sync_count += 1;
+        }
+
+        self.spawn_async(event, args, callbacks)
+    }
+
+    #[cfg(feature = "async")]
+    fn spawn_async(
+        &self,
+        event: &K::Ref,
+        args: ThreadSafeArgs,
+        callbacks: Vec<(HandlerId, ThreadSafeCallback, Option<Arc<Semaphore>>)>,
+    ) -> Vec<Spawned> {
+        let mut tasks = Vec::new();
+        let mut spawner = None;
+        let mut lane = None;
+        for (id, callback, semaphore) in callbacks {
+            let args_clone = args.clone();
+            let fut: ThreadSafeHandlerFuture = match callback {
+                ThreadSafeCallback::Async(cb) => Box::pin(async move {
+                    cb(args_clone).await;
+                }),
+                ThreadSafeCallback::TryAsync(cb) => {
+                    let emitter = self.clone();
+                    let event = event.to_owned();
+                    Box::pin(async move {
+                        if let Err(error) = cb(args_clone).await {
+                            emitter.emit_error(key_ref(&event), id, error);
+                        }
+                    })
+                }
+                ThreadSafeCallback::AsyncPattern(cb) => {
+                    let event_name = K::name(event).into_owned();
+                    Box::pin(async move {
+                        cb(&event_name, args_clone).await;
+                    })
+                }
+                ThreadSafeCallback::Sync(_)
+                | ThreadSafeCallback::TrySync(_)
+                | ThreadSafeCallback::SyncPattern(_) => continue,
+            };
+            // Resolved on the first async handler, so sync-only emits never
+            // start the internal runtime.
+            match spawner.get_or_insert_with(|| self.runtime_spawner()) {
+                Some(spawner) => {
+                    let lane = lane.get_or_insert_with(|| self.reserve_lane(event));
+                    let spawned =
+                        self.spawn_handler(&**spawner, event, lane.as_mut(), id, semaphore, fut);
+                    tasks.push(spawned);
+                }
+                None => self.without_runtime(event, id, semaphore, fut),
+            }
+        }
+
+        tasks
+    }
+
+    /// Async listeners are compiled out, so there is nothing to spawn.
+    #[cfg(not(feature = "async"))]
+    fn spawn_async(
+        &self,
+        _event: &K::Ref,
+        _args: ThreadSafeArgs,
+        _callbacks: Vec<(HandlerId, ThreadSafeCallback, Option<Arc<Semaphore>>)>,
+    ) -> Vec<Spawned> {
+        Vec::new()
+    }
+
+    /// The attached spawner, else the runtime the caller is on.
+    #[cfg(feature = "async")]
+    fn spawner(&self) -> Option<Arc<dyn Spawner>> {
+        let attached = self.lock_runtime().spawner.clone();
+        attached.or_else(runtime::current_spawner)
+    }
+
+    /// Like `spawner`, falling back to the internal runtime under
+    /// `MissingRuntime::Internal`.
+    #[cfg(feature = "async")]
+    fn runtime_spawner(&self) -> Option<Arc<dyn Spawner>> {
+        self.spawner().or_else(|| match self.config().missing_runtime {
+            MissingRuntime::Internal => runtime::internal_spawner(),
+            _ => None,
+        })
+    }
+
+    /// Timer of the spawner, used for deadlines and timeouts; `None` without
+    /// a runtime.
+    #[cfg(feature = "async")]
+    pub(crate) fn sleep(&self, duration: Duration) -> Option<ThreadSafeHandlerFuture> {
+        self.runtime_spawner().map(|spawner| spawner.sleep(duration))
+    }
+
+    #[cfg(feature = "async")]
+    fn spawn_handler(
+        &self,
+        spawner: &dyn Spawner,
+        event: &K::Ref,
+        lane: Option<&mut LaneSlot>,
+        id: HandlerId,
+        semaphore: Option<Arc<Semaphore>>,
+        fut: ThreadSafeHandlerFuture,
+    ) -> Spawned {
+        let fut = self.guard_task(event, id, fut);
+        let (task, started) = self.schedule(event, lane, semaphore, fut);
+        let (task, handle, outcome) = abortable(task);
+        spawner.spawn(Box::pin(self.tracker.track(task)));
+        self.lock_in_flight().track(event, id, handle);
+        Spawned { id, outcome, started }
+    }
+
+    #[cfg(feature = "async")]
+    fn without_runtime(
+        &self,
+        event: &K::Ref,
+        id: HandlerId,
+        semaphore: Option<Arc<Semaphore>>,
+        fut: ThreadSafeHandlerFuture,
+    ) {
+        match self.config().missing_runtime {
+            MissingRuntime::Error | MissingRuntime::Internal => {
+                self.report_no_runtime(event, id);
+            }
+            MissingRuntime::Panic => {
+                panic!("async listener {id} of '{}': {NoRuntime}", K::name(event))
+            }
+            MissingRuntime::Queue => self.lock_runtime().queued.push(QueuedHandler {
+                event: event.to_owned(),
+                id,
+                semaphore,
+                fut,
+            }),
+        }
+    }
+}
+
+impl<K> ThreadSafeEventEmitter<K> for MultiThreadEventEmitter<K>
+where
+    K: EventKey<Ref: Sync> + Send + Sync,
+{
+    #[track_caller]
+    fn on<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        self.on_with(event, ListenerOptions::new(), callback)
+    }
+
+    #[track_caller]
+    fn once<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        self.on_with(event, ListenerOptions::new().once(), callback)
+    }
+
+    #[track_caller]
+    fn on_with<F>(&self, event: &K::Ref, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        registered(self.add_listener(event, options, callback))
+    }
+
+    #[track_caller]
+    fn add_listener<F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        callback: F,
+    ) -> Result<HandlerId, MaxListenersExceeded>
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        self.add_handler(event, ThreadSafeCallback::Sync(Arc::new(callback)), options, None)
+    }
+
+    #[track_caller]
+    fn on_filtered_with<P, F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        predicate: P,
+        callback: F,
+    ) -> HandlerId
+    where
+        P: Fn(&ThreadSafeArgs) -> bool + Send + Sync + 'static,
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        let callback = ThreadSafeCallback::Sync(Arc::new(callback));
+        registered(self.add_handler(event, callback, options, Some(Arc::new(predicate))))
+    }
+
+    #[track_caller]
+    fn on_pattern_with<F>(&self, pattern: &str, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(&str, ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        let callback = ThreadSafeCallback::SyncPattern(Arc::new(callback));
+        registered(self.add_pattern_handler(pattern, callback, options))
+    }
+
+    #[track_caller]
+    fn try_on<F, E>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Result<(), E> + Send + Sync + 'static,
+        E: Into<ThreadSafeHandlerError>,
+    {
+        let callback: TrySyncThreadSafeCallback =
+            Arc::new(move |args| callback(args).map_err(Into::into));
+        registered(self.add_handler(
+            event,
+            ThreadSafeCallback::TrySync(callback),
+            ListenerOptions::new(),
+            None,
+        ))
+    }
+
+    fn off(&self, event: &K::Ref, id: HandlerId) -> bool {
+        #[cfg(feature = "async")]
+        if self.remove_responder(event, id) {
+            self.lock_in_flight().cancel(id, self.config().cancel_policy);
+            return true;
+        }
+
+        let removed = self.remove_handler(event, id);
+        if removed {
+            #[cfg(feature = "async")]
+            self.lock_in_flight().cancel(id, self.config().cancel_policy);
+            self.emit_meta(K::remove_listener_event(), event, id);
+        }
+        removed
+    }
+
+    fn off_all(&self, event: &K::Ref) {
+        let mut removed = self.lock_listeners().remove(event).unwrap_or_default();
+
+        {
+            let mut patterns = self.lock_patterns();
+            if !patterns.is_empty()
+                && let Some(handlers) = patterns.get_mut(&K::name(event), self.config().delimiter)
+            {
+                removed.append(handlers);
+            }
+        }
+
+        for handler in removed {
+            #[cfg(feature = "async")]
+            self.lock_in_flight().cancel(handler.id, self.config().cancel_policy);
+            self.emit_meta(K::remove_listener_event(), event, handler.id);
         }
     }
+
+    fn emit(&self, event: &K::Ref, args: Arc<Vec<ThreadSafeArg>>) {
+        if self.reject_emit() {
+            return;
+        }
+        self.dispatch(event, args, &mut EmitSummary::default());
+    }
+
+    fn event_names(&self) -> Vec<K> {
+        self
+            .lock_listeners()
+            .iter()
+            .filter(|(_, handlers)| !handlers.is_empty())
+            .map(|(event, _)| event.clone())
+            .collect()
+    }
+
+    fn event_patterns(&self) -> Vec<String> {
+        self.lock_patterns().patterns(self.config().delimiter)
+    }
+
+    fn listeners(&self, event: &K::Ref) -> Vec<ListenerInfo> {
+        self.matching(event, ThreadSafeHandler::info)
+    }
+
+    fn raw_listeners(&self, event: &K::Ref) -> Vec<ListenerInfo> {
+        let mut infos: Vec<ListenerInfo> = self
+            .lock_listeners()
+            .get(event)
+            .map(|handlers| handlers.iter().map(ThreadSafeHandler::info).collect())
+            .unwrap_or_default();
+        let patterns = self.lock_patterns();
+        if !patterns.is_empty()
+            && let Some(handlers) = patterns.get(&K::name(event), self.config().delimiter)
+        {
+            infos.extend(handlers.iter().map(ThreadSafeHandler::info));
+        }
+        infos
+    }
 }
 
-impl ThreadSafeAsyncEventEmitter for MultiThreadEventEmitter {
-    fn on_async<F>(&self, event: &str, callback: F) -> HandlerId
+#[cfg(feature = "async")]
+impl<K> ThreadSafeAsyncEventEmitter<K> for MultiThreadEventEmitter<K>
+where
+    K: EventKey<Ref: Sync> + Send + Sync,
+{
+    #[track_caller]
+    fn on_async<F>(&self, event: &K::Ref, callback: F) -> HandlerId
     where
         F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
             + Send
             + Sync
             + 'static,
     {
-        let id = self.get_id();
-        let handler = ThreadSafeHandler {
-            id,
-            callback: ThreadSafeCallback::Async(Arc::new(callback)),
-            once: false,
-        };
+        self.on_async_with(event, ListenerOptions::new(), callback)
+    }
 
-        self.listeners
-            .lock()
-            .unwrap()
-            .entry(event.to_string())
-            .or_default()
-            .push(handler);
+    #[track_caller]
+    fn once_async<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_async_with(event, ListenerOptions::new().once(), callback)
+    }
 
-        id
+    #[track_caller]
+    fn on_async_with<F>(&self, event: &K::Ref, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        registered(self.add_async_listener(event, options, callback))
     }
 
-    fn once_async<F>(&self, event: &str, callback: F) -> HandlerId
+    #[track_caller]
+    fn add_async_listener<F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        callback: F,
+    ) -> Result<HandlerId, MaxListenersExceeded>
     where
         F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
             + Send
             + Sync
             + 'static,
     {
-        let id = self.get_id();
-        let handler = ThreadSafeHandler {
-            id,
-            callback: ThreadSafeCallback::Async(Arc::new(callback)),
-            once: true,
-        };
+        self.add_handler(event, ThreadSafeCallback::Async(Arc::new(callback)), options, None)
+    }
 
-        self.listeners
-            .lock()
-            .unwrap()
-            .entry(event.to_string())
-            .or_default()
-            .push(handler);
+    #[track_caller]
+    fn on_filtered_async_with<P, F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        predicate: P,
+        callback: F,
+    ) -> HandlerId
+    where
+        P: Fn(&ThreadSafeArgs) -> bool + Send + Sync + 'static,
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let callback = ThreadSafeCallback::Async(Arc::new(callback));
+        registered(self.add_handler(event, callback, options, Some(Arc::new(predicate))))
+    }
+
+    #[track_caller]
+    fn on_pattern_async_with<F>(
+        &self,
+        pattern: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> HandlerId
+    where
+        F: Fn(&str, ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let callback = ThreadSafeCallback::AsyncPattern(Arc::new(callback));
+        registered(self.add_pattern_handler(pattern, callback, options))
+    }
+
+    #[track_caller]
+    fn try_on_async<F, E>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = Result<(), E>> + Send>>
+            + Send
+            + Sync
+            + 'static,
+        E: Into<ThreadSafeHandlerError> + 'static,
+    {
+        let callback: TryAsyncThreadSafeCallback = Arc::new(move |args| {
+            let fut = callback(args);
+            Box::pin(async move { fut.await.map_err(Into::into) })
+        });
+        registered(self.add_handler(
+            event,
+            ThreadSafeCallback::TryAsync(callback),
+            ListenerOptions::new(),
+            None,
+        ))
+    }
 
+    #[track_caller]
+    fn on_async_cancellable<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs, CancellationToken) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let token = CancellationToken::new();
+        let listener_token = token.clone();
+        let id = self.on_async(event, move |args| callback(args, listener_token.clone()));
+        self.lock_in_flight().insert_token(id, token);
         id
     }
+
+    async fn emit_and_wait(&self, event: &K::Ref, args: Arc<Vec<ThreadSafeArg>>) -> EmitSummary {
+        let mut summary = EmitSummary::default();
+        if self.reject_emit() {
+            return summary;
+        }
+        let tasks = self.dispatch(event, args, &mut summary);
+
+        for Spawned { id, outcome, .. } in tasks {
+            summary.async_count += 1;
+            if outcome.await == Ok(TaskOutcome::Panicked) {
+                summary.panicked.push(id);
+            }
+        }
+
+        summary
+    }
+
+    async fn emit_async(&self, event: &K::Ref, args: Arc<Vec<ThreadSafeArg>>) {
+        if self.reject_emit() {
+            return;
+        }
+        let tasks = self.dispatch(event, args, &mut EmitSummary::default());
+        for Spawned { started, .. } in tasks {
+            let _ = started.await;
+        }
+    }
 }
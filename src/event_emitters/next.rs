@@ -0,0 +1,117 @@
+use std::cell::RefCell;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::event_emitters::*;
+use crate::runtime;
+use crate::types::*;
+
+async fn with_timeout<F, S, T>(sleep: Option<S>, future: F) -> Result<T, NextError>
+where
+    F: Future<Output = Result<T, NextError>>,
+    S: Future<Output = ()>,
+{
+    let Some(sleep) = sleep else {
+        return Err(NextError::NoRuntime);
+    };
+    runtime::timeout(sleep, future)
+        .await
+        .unwrap_or(Err(NextError::Timeout))
+}
+
+impl<K: EventKey> SingleThreadEventEmitter<K> {
+    /// Resolves with the args of the next emit of `event`, like Node's
+    /// `events.once`. The hidden listener is registered immediately and removed
+    /// when the future completes or is dropped.
+    pub fn next(&self, event: &K::Ref) -> impl Future<Output = Result<Args, NextError>> + 'static {
+        self.next_matching(event, |_| true)
+    }
+
+    /// Like `next`, but ignores emits whose args do not satisfy `predicate`.
+    pub fn next_matching<P>(
+        &self,
+        event: &K::Ref,
+        predicate: P,
+    ) -> impl Future<Output = Result<Args, NextError>> + 'static
+    where
+        P: Fn(&Args) -> bool + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let tx = RefCell::new(Some(tx));
+        let subscription = self.subscribe(event, move |args| {
+            if predicate(&args) {
+                let tx = tx.borrow_mut().take();
+                if let Some(tx) = tx {
+                    let _ = tx.send(args);
+                }
+            }
+        });
+
+        async move {
+            let result = rx.await.map_err(|_| NextError::Removed);
+            drop(subscription);
+            result
+        }
+    }
+
+    /// Like `next`, but fails with `NextError::Timeout` after `timeout`, or
+    /// with `NextError::NoRuntime` when there is no spawner to time it.
+    pub fn next_timeout(
+        &self,
+        event: &K::Ref,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<Args, NextError>> + 'static {
+        with_timeout(self.sleep(timeout), self.next(event))
+    }
+}
+
+impl<K: EventKey<Ref: Sync> + Send + Sync> MultiThreadEventEmitter<K> {
+    /// Resolves with the args of the next emit of `event`, like Node's
+    /// `events.once`. The hidden listener is registered immediately and removed
+    /// when the future completes or is dropped.
+    pub fn next(
+        &self,
+        event: &K::Ref,
+    ) -> impl Future<Output = Result<ThreadSafeArgs, NextError>> + Send + 'static {
+        self.next_matching(event, |_| true)
+    }
+
+    /// Like `next`, but ignores emits whose args do not satisfy `predicate`.
+    pub fn next_matching<P>(
+        &self,
+        event: &K::Ref,
+        predicate: P,
+    ) -> impl Future<Output = Result<ThreadSafeArgs, NextError>> + Send + 'static
+    where
+        P: Fn(&ThreadSafeArgs) -> bool + Send + Sync + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let tx = Mutex::new(Some(tx));
+        let subscription = self.subscribe(event, move |args| {
+            if predicate(&args) {
+                let tx = tx.lock().unwrap_or_else(PoisonError::into_inner).take();
+                if let Some(tx) = tx {
+                    let _ = tx.send(args);
+                }
+            }
+        });
+
+        async move {
+            let result = rx.await.map_err(|_| NextError::Removed);
+            drop(subscription);
+            result
+        }
+    }
+
+    /// Like `next`, but fails with `NextError::Timeout` after `timeout`, or
+    /// with `NextError::NoRuntime` when there is no runtime to time it.
+    pub fn next_timeout(
+        &self,
+        event: &K::Ref,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<ThreadSafeArgs, NextError>> + Send + 'static {
+        with_timeout(self.sleep(timeout), self.next(event))
+    }
+}
@@ -0,0 +1,113 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use crate::event_emitters::*;
+use crate::runtime;
+use crate::types::*;
+
+/// Answers one request: gets the caller's args and the channel for its reply.
+pub(crate) type Responder = Arc<
+    dyn Fn(ThreadSafeArgs, mpsc::UnboundedSender<ThreadSafeArg>) -> ThreadSafeHandlerFuture
+        + Send
+        + Sync,
+>;
+
+/// Responders of each event, kept apart from its listeners.
+pub(crate) type Responders<K> = HashMap<K, Vec<(HandlerId, Responder)>>;
+
+impl<K: EventKey<Ref: Sync> + Send + Sync> MultiThreadEventEmitter<K> {
+    /// Registers a responder for requests on `event`. Responders are not
+    /// listeners: plain emits of `event` never reach them, and `off` with the
+    /// returned ID removes one.
+    pub fn handle<F, Fut, R>(&self, event: &K::Ref, responder: F) -> HandlerId
+    where
+        F: Fn(ThreadSafeArgs) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = R> + Send + 'static,
+        R: Any + Send + Sync,
+    {
+        self.add_responder(
+            event,
+            Arc::new(move |args, reply| {
+                let fut = responder(args);
+                Box::pin(async move {
+                    let _ = reply.send(Box::new(fut.await));
+                })
+            }),
+        )
+    }
+
+    /// Emits a request on `event` and resolves with the first reply of type `R`.
+    /// Fails with `RequestError::NoRuntime`, sending nothing, when there is no
+    /// runtime to time it.
+    pub async fn request<R>(
+        &self,
+        event: &K::Ref,
+        args: ThreadSafeArgs,
+        timeout: Duration,
+    ) -> Result<R, RequestError>
+    where
+        R: Any + Send,
+    {
+        let Some(sleep) = self.sleep(timeout) else {
+            return Err(RequestError::NoRuntime);
+        };
+        let mut replies = self.send_request(event, args);
+        let first = async {
+            while let Some(reply) = replies.recv().await {
+                if let Ok(reply) = reply.downcast::<R>() {
+                    return Ok(*reply);
+                }
+            }
+            Err(RequestError::NoResponder)
+        };
+
+        runtime::timeout(sleep, first)
+            .await
+            .unwrap_or(Err(RequestError::Timeout))
+    }
+
+    /// Scatter-gather: emits a request on `event` and collects every reply of
+    /// type `R` until `deadline` passes or all responders have finished. Fails
+    /// like `request` when there is no runtime.
+    pub async fn gather<R>(
+        &self,
+        event: &K::Ref,
+        args: ThreadSafeArgs,
+        deadline: Duration,
+    ) -> Result<Vec<R>, RequestError>
+    where
+        R: Any + Send,
+    {
+        let Some(sleep) = self.sleep(deadline) else {
+            return Err(RequestError::NoRuntime);
+        };
+        let mut replies = self.send_request(event, args);
+        let mut collected = Vec::new();
+        let collect = async {
+            while let Some(reply) = replies.recv().await {
+                if let Ok(reply) = reply.downcast::<R>() {
+                    collected.push(*reply);
+                }
+            }
+        };
+
+        let _ = runtime::timeout(sleep, collect).await;
+        Ok(collected)
+    }
+
+    /// The channel closes once every responder is done with the request and
+    /// has dropped its sender.
+    fn send_request(
+        &self,
+        event: &K::Ref,
+        args: ThreadSafeArgs,
+    ) -> mpsc::UnboundedReceiver<ThreadSafeArg> {
+        let (reply, replies) = mpsc::unbounded_channel();
+        self.spawn_responders(event, |responder| responder(args.clone(), reply.clone()));
+        replies
+    }
+}
@@ -1,186 +1,1378 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::pin::Pin;
-use std::rc::Rc;
+use alloc::borrow::{Cow, ToOwned};
+#[cfg(feature = "async")]
+use alloc::boxed::Box;
+use alloc::rc::{Rc, Weak};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::cmp::Reverse;
+#[cfg(feature = "async")]
+use core::pin::Pin;
+#[cfg(feature = "async")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::panic::{self, AssertUnwindSafe};
 
+use hashbrown::HashMap;
+
+#[cfg(feature = "async")]
+use tokio::sync::{Semaphore, oneshot};
+
+use crate::args;
 use crate::basis::*;
+#[cfg(feature = "async")]
+use crate::runtime::{self, LocalSpawner};
+#[cfg(feature = "async")]
+use crate::tasks::*;
+use crate::topic::TopicTrie;
 use crate::types::*;
 
-pub trait EventEmitter {
-    fn on<F>(&self, event: &str, callback: F) -> HandlerId
+pub trait EventEmitter<K: EventKey = String> {
+    fn on<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) + 'static;
+
+    fn once<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) + 'static;
+
+    fn on_with<F>(&self, event: &K::Ref, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(Args) + 'static;
+
+    /// Registers a listener that is only called for emits whose args satisfy
+    /// `predicate`.
+    #[track_caller]
+    fn on_filtered<P, F>(&self, event: &K::Ref, predicate: P, callback: F) -> HandlerId
+    where
+        P: Fn(&Args) -> bool + 'static,
+        F: Fn(Args) + 'static,
+    {
+        self.on_filtered_with(event, ListenerOptions::new(), predicate, callback)
+    }
+
+    /// Filtered once listener: it is removed after the first emit that matches
+    /// `predicate`, not after the first emit.
+    #[track_caller]
+    fn once_filtered<P, F>(&self, event: &K::Ref, predicate: P, callback: F) -> HandlerId
+    where
+        P: Fn(&Args) -> bool + 'static,
+        F: Fn(Args) + 'static,
+    {
+        self.on_filtered_with(event, ListenerOptions::new().once(), predicate, callback)
+    }
+
+    fn on_filtered_with<P, F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        predicate: P,
+        callback: F,
+    ) -> HandlerId
     where
+        P: Fn(&Args) -> bool + 'static,
         F: Fn(Args) + 'static;
 
-    fn once<F>(&self, event: &str, callback: F) -> HandlerId
+    /// Like `on_with`, but returns the error instead of panicking when strict
+    /// max-listeners mode rejects the registration.
+    fn add_listener<F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        callback: F,
+    ) -> Result<HandlerId, MaxListenersExceeded>
     where
         F: Fn(Args) + 'static;
 
-    fn off(&self, event: &str, id: HandlerId) -> bool;
+    #[track_caller]
+    fn prepend_listener<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) + 'static,
+    {
+        self.on_with(event, ListenerOptions::new().prepend(), callback)
+    }
+
+    #[track_caller]
+    fn prepend_once_listener<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) + 'static,
+    {
+        self.on_with(event, ListenerOptions::new().once().prepend(), callback)
+    }
+
+    /// Subscribes to every event matching `pattern`, e.g. `"order.*"` or
+    /// `"sensor.#"`. The callback receives the concrete event name.
+    #[track_caller]
+    fn on_pattern<F>(&self, pattern: &str, callback: F) -> HandlerId
+    where
+        F: Fn(&str, Args) + 'static,
+    {
+        self.on_pattern_with(pattern, ListenerOptions::new(), callback)
+    }
+
+    fn on_pattern_with<F>(&self, pattern: &str, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(&str, Args) + 'static;
+
+    /// Registers a fallible listener. An `Err` is emitted on the `"error"` event,
+    /// or panics out of `emit` when nothing listens to `"error"`.
+    fn try_on<F, E>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) -> Result<(), E> + 'static,
+        E: Into<HandlerError>;
+
+    fn off(&self, event: &K::Ref, id: HandlerId) -> bool;
+
+    fn off_all(&self, event: &K::Ref);
+
+    fn emit(&self, event: &K::Ref, args: Rc<Vec<Arg>>);
+
+    /// Number of listeners an emit of `event` would call, pattern listeners included.
+    fn listener_count(&self, event: &K::Ref) -> usize {
+        self.listeners(event).len()
+    }
+
+    /// Events that currently have listeners, in no particular order.
+    fn event_names(&self) -> Vec<K>;
+
+    /// Patterns that currently have listeners, written with `*` and `#`.
+    fn event_patterns(&self) -> Vec<String>;
 
-    fn off_all(&self, event: &str);
+    /// Listeners an emit of `event` would call, in dispatch order.
+    fn listeners(&self, event: &K::Ref) -> Vec<ListenerInfo>;
 
-    fn emit(&self, event: &str, args: Rc<Vec<Arg>>);
+    /// Listeners registered under exactly `event`, which may be a pattern.
+    fn raw_listeners(&self, event: &K::Ref) -> Vec<ListenerInfo>;
 }
 
-pub trait AsyncEventEmitter {
-    fn on_async<F>(&self, event: &str, callback: F) -> HandlerId
+#[cfg(feature = "async")]
+pub trait AsyncEventEmitter<K: EventKey = String> {
+    fn on_async<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static;
+
+    fn once_async<F>(&self, event: &K::Ref, callback: F) -> HandlerId
     where
         F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static;
 
-    fn once_async<F>(&self, event: &str, callback: F) -> HandlerId
+    fn on_async_with<F>(&self, event: &K::Ref, options: ListenerOptions, callback: F) -> HandlerId
     where
         F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static;
+
+    /// Async counterpart of `on_filtered`.
+    #[track_caller]
+    fn on_filtered_async<P, F>(&self, event: &K::Ref, predicate: P, callback: F) -> HandlerId
+    where
+        P: Fn(&Args) -> bool + 'static,
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        self.on_filtered_async_with(event, ListenerOptions::new(), predicate, callback)
+    }
+
+    /// Async counterpart of `once_filtered`.
+    #[track_caller]
+    fn once_filtered_async<P, F>(&self, event: &K::Ref, predicate: P, callback: F) -> HandlerId
+    where
+        P: Fn(&Args) -> bool + 'static,
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        self.on_filtered_async_with(event, ListenerOptions::new().once(), predicate, callback)
+    }
+
+    fn on_filtered_async_with<P, F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        predicate: P,
+        callback: F,
+    ) -> HandlerId
+    where
+        P: Fn(&Args) -> bool + 'static,
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static;
+
+    /// Like `on_async_with`, but returns the error instead of panicking when
+    /// strict max-listeners mode rejects the registration.
+    fn add_async_listener<F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        callback: F,
+    ) -> Result<HandlerId, MaxListenersExceeded>
+    where
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static;
+
+    #[track_caller]
+    fn prepend_async_listener<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        self.on_async_with(event, ListenerOptions::new().prepend(), callback)
+    }
+
+    #[track_caller]
+    fn prepend_once_async_listener<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        self.on_async_with(event, ListenerOptions::new().once().prepend(), callback)
+    }
+
+    #[track_caller]
+    fn on_pattern_async<F>(&self, pattern: &str, callback: F) -> HandlerId
+    where
+        F: Fn(&str, Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        self.on_pattern_async_with(pattern, ListenerOptions::new(), callback)
+    }
+
+    fn on_pattern_async_with<F>(
+        &self,
+        pattern: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> HandlerId
+    where
+        F: Fn(&str, Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static;
+
+    /// Async counterpart of `try_on`. An unhandled error panics the spawned task.
+    fn try_on_async<F, E>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = Result<(), E>>>> + 'static,
+        E: Into<HandlerError> + 'static;
+
+    /// Like `on_async`, but each call also gets the listener's cancellation
+    /// token, which is cancelled when the listener is removed by `off` / `off_all`.
+    fn on_async_cancellable<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args, CancellationToken) -> Pin<Box<dyn Future<Output = ()>>> + 'static;
+
+    /// Emits like `emit`, then waits until every async handler has finished.
+    /// With tokio, must be awaited inside a `LocalSet`.
+    fn emit_and_wait(
+        &self,
+        event: &K::Ref,
+        args: Rc<Vec<Arg>>,
+    ) -> impl Future<Output = EmitSummary>;
+
+    /// Emits like `emit`, then waits until every async handler has got past
+    /// its concurrency limits, so a producer slows down to the pace of the
+    /// handlers instead of queueing work without bound.
+    /// With tokio, must be awaited inside a `LocalSet`.
+    fn emit_async(&self, event: &K::Ref, args: Rc<Vec<Arg>>) -> impl Future<Output = ()>;
+}
+
+#[derive(Clone)]
+pub struct SingleThreadEventEmitter<K: EventKey = String> {
+    inner: Rc<Inner<K>>,
+}
+
+/// Everything an emitter owns. Clones share it and weak handles point at it,
+/// so a weak handle never keeps any part of the emitter alive.
+struct Inner<K: EventKey> {
+    listeners: RefCell<HashMap<K, Vec<Handler>>>,
+    patterns: RefCell<TopicTrie<Handler>>,
+    id_counter: RefCell<HandlerId>,
+    retained: RefCell<HashMap<K, Retained<Args>>>,
+    #[cfg(feature = "async")]
+    lanes: RefCell<HashMap<K, oneshot::Receiver<()>>>,
+    #[cfg(feature = "async")]
+    in_flight: RefCell<InFlight<K>>,
+    #[cfg(feature = "async")]
+    tracker: TaskCounter,
+    shutdown: RefCell<ShutdownState>,
+    config: RefCell<Rc<Config<K>>>,
+}
+
+/// Settings made through the builder-style setters, shared by every clone.
+#[derive(Clone)]
+struct Config<K: EventKey> {
+    #[cfg(feature = "async")]
+    spawner: Option<Rc<dyn LocalSpawner>>,
+    #[cfg(feature = "std")]
+    panic_hook: Option<PanicHook>,
+    limits: ListenerLimits<K, MaxListenersHook>,
+    #[cfg(feature = "async")]
+    dispatch_modes: DispatchModes<K>,
+    #[cfg(feature = "async")]
+    cancel_policy: CancelPolicy,
+    #[cfg(feature = "async")]
+    concurrency: ConcurrencyLimits<K>,
+    interceptors: Vec<Rc<dyn Interceptor<K>>>,
+    meta_events: bool,
+    delimiter: char,
 }
 
+/// Non-owning handle to a [`SingleThreadEventEmitter`].
 #[derive(Clone)]
-pub struct SingleThreadEventEmitter {
-    listeners: Rc<RefCell<HashMap<String, Vec<Handler>>>>,
-    id_counter: Rc<RefCell<HandlerId>>,
+pub struct WeakSingleThreadEventEmitter<K: EventKey = String> {
+    inner: Weak<Inner<K>>,
+}
+
+impl<K: EventKey> WeakSingleThreadEventEmitter<K> {
+    pub fn upgrade(&self) -> Option<SingleThreadEventEmitter<K>> {
+        let inner = self.inner.upgrade()?;
+        Some(SingleThreadEventEmitter { inner })
+    }
 }
 
 impl SingleThreadEventEmitter {
+    /// Emitter keyed by event names. For another [`EventKey`] use
+    /// `SingleThreadEventEmitter::<K>::default()`.
     pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K: EventKey> Default for SingleThreadEventEmitter<K> {
+    fn default() -> Self {
+        let config = Config {
+            #[cfg(feature = "async")]
+            spawner: None,
+            #[cfg(feature = "std")]
+            panic_hook: None,
+            limits: ListenerLimits::default(),
+            #[cfg(feature = "async")]
+            dispatch_modes: DispatchModes::default(),
+            #[cfg(feature = "async")]
+            cancel_policy: CancelPolicy::default(),
+            #[cfg(feature = "async")]
+            concurrency: ConcurrencyLimits::default(),
+            interceptors: Vec::new(),
+            meta_events: false,
+            delimiter: '.',
+        };
+        let inner = Inner {
+            listeners: RefCell::new(HashMap::new()),
+            patterns: RefCell::new(TopicTrie::new()),
+            id_counter: RefCell::new(1),
+            retained: RefCell::new(HashMap::new()),
+            #[cfg(feature = "async")]
+            lanes: RefCell::new(HashMap::new()),
+            #[cfg(feature = "async")]
+            in_flight: RefCell::new(InFlight::default()),
+            #[cfg(feature = "async")]
+            tracker: TaskCounter::default(),
+            shutdown: RefCell::new(ShutdownState::default()),
+            config: RefCell::new(Rc::new(config)),
+        };
         Self {
-            listeners: Rc::new(RefCell::new(HashMap::new())),
-            id_counter: Rc::new(RefCell::new(1)),
+            inner: Rc::new(inner),
         }
     }
+}
 
-    fn get_id(&self) -> HandlerId {
-        let mut id = self.id_counter.borrow_mut();
-        let old_value = *id;
-        *id += 1;
-        old_value
+impl<K: EventKey> SingleThreadEventEmitter<K> {
+    /// Warns once an event has more than `n` listeners; `0` means unlimited.
+    pub fn set_max_listeners(self, n: usize) -> Self {
+        self.configure(|config| config.limits.default_limit = Some(n))
     }
-}
 
-impl EventEmitter for SingleThreadEventEmitter {
-    fn on<F>(&self, event: &str, callback: F) -> HandlerId
+    /// Overrides the max-listeners limit for a single event or pattern.
+    pub fn set_event_max_listeners(self, event: &K::Ref, n: usize) -> Self {
+        self.configure(|config| {
+            config.limits.per_event.insert(event.to_owned(), n);
+        })
+    }
+
+    /// Called instead of printing to stderr when a limit is exceeded.
+    pub fn set_max_listeners_hook<F>(self, hook: F) -> Self
     where
-        F: Fn(Args) + 'static,
+        F: Fn(&MaxListenersExceeded) + 'static,
     {
-        let id = self.get_id();
-        let handler = Handler {
-            id,
-            callback: Callback::Sync(Rc::new(callback)),
-            once: false,
-        };
+        self.configure(|config| config.limits.hook = Some(Rc::new(hook)))
+    }
+
+    /// In strict mode a registration over the limit fails: `add_listener`
+    /// returns the error and the other registration methods panic with it.
+    pub fn set_strict_max_listeners(self, strict: bool) -> Self {
+        self.configure(|config| config.limits.strict = strict)
+    }
+
+    /// How async handlers run; `DispatchMode::Concurrent` by default.
+    #[cfg(feature = "async")]
+    pub fn set_dispatch_mode(self, mode: DispatchMode) -> Self {
+        self.configure(|config| config.dispatch_modes.default_mode = mode)
+    }
+
+    /// Overrides the dispatch mode for a single event.
+    #[cfg(feature = "async")]
+    pub fn set_event_dispatch_mode(self, event: &K::Ref, mode: DispatchMode) -> Self {
+        self.configure(|config| {
+            config
+                .dispatch_modes
+                .per_event
+                .insert(event.to_owned(), mode);
+        })
+    }
 
-        self.listeners
+    /// Caps how many async handlers of this emitter run at once; further
+    /// handlers wait in a queue until a slot frees up. 0 means unlimited.
+    #[cfg(feature = "async")]
+    pub fn set_max_concurrency(self, n: usize) -> Self {
+        self.configure(|config| config.concurrency.set_emitter(n))
+    }
+
+    /// Caps how many async handlers of `event` run at once. 0 means unlimited.
+    #[cfg(feature = "async")]
+    pub fn set_event_max_concurrency(self, event: &K::Ref, n: usize) -> Self {
+        self.configure(|config| config.concurrency.set_event(event, n))
+    }
+
+    /// What `off` / `off_all` do with async tasks the removed listener still
+    /// has running; `CancelPolicy::Detach` by default.
+    #[cfg(feature = "async")]
+    pub fn set_cancel_policy(self, policy: CancelPolicy) -> Self {
+        self.configure(|config| config.cancel_policy = policy)
+    }
+
+    /// Runs async listeners on `spawner` instead of the default: tokio's
+    /// `spawn_local`, else async-std's when only that backend is enabled.
+    #[cfg(feature = "async")]
+    pub fn set_local_spawner<S>(self, spawner: S) -> Self
+    where
+        S: LocalSpawner,
+    {
+        self.configure(|config| config.spawner = Some(Rc::new(spawner)))
+    }
+
+    /// Appends an interceptor to the emit pipeline; see [`Interceptor`].
+    pub fn with_interceptor<I>(self, interceptor: I) -> Self
+    where
+        I: Interceptor<K> + 'static,
+    {
+        self.configure(|config| config.interceptors.push(Rc::new(interceptor)))
+    }
+
+    /// Emits `"newListener"` / `"removeListener"` whenever a listener is added
+    /// or removed, including once listeners removed after firing.
+    pub fn set_meta_events(self, enabled: bool) -> Self {
+        self.configure(|config| config.meta_events = enabled)
+    }
+
+    /// Keeps payloads of `event` according to `policy` and replays them to
+    /// every listener later added for `event`, before any live emit.
+    pub fn set_retention(self, event: &K::Ref, policy: Retention) -> Self {
+        self.inner
+            .retained
             .borrow_mut()
-            .entry(event.to_string())
-            .or_default()
-            .push(handler);
+            .insert(event.to_owned(), Retained::new(policy));
+        self
+    }
 
-        id
+    /// Forgets the payloads retained for `event`; its policy stays in place.
+    pub fn clear_retained(&self, event: &K::Ref) {
+        if let Some(retained) = self.inner.retained.borrow_mut().get_mut(event) {
+            retained.clear();
+        }
+    }
+
+    /// Segment separator used by pattern subscriptions, `'.'` by default.
+    pub fn set_delimiter(self, delimiter: char) -> Self {
+        self.configure(|config| config.delimiter = delimiter)
     }
 
-    fn once<F>(&self, event: &str, callback: F) -> HandlerId
+    /// Enables panic isolation: a panicking listener is reported to `hook`
+    /// and the remaining listeners still run.
+    #[cfg(feature = "std")]
+    pub fn set_panic_hook<F>(self, hook: F) -> Self
     where
-        F: Fn(Args) + 'static,
+        F: Fn(&ListenerPanic) + 'static,
     {
-        let id = self.get_id();
-        let handler = Handler {
-            id,
-            callback: Callback::Sync(Rc::new(callback)),
-            once: true,
+        self.configure(|config| config.panic_hook = Some(Rc::new(hook)))
+    }
+
+    /// Applies a setting to the configuration shared by every clone.
+    fn configure<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut Config<K>),
+    {
+        f(Rc::make_mut(&mut self.inner.config.borrow_mut()));
+        self
+    }
+
+    /// Snapshot of the configuration; no borrow is held while it is used, so
+    /// listeners and hooks may reconfigure the emitter.
+    fn config(&self) -> Rc<Config<K>> {
+        self.inner.config.borrow().clone()
+    }
+
+    pub fn downgrade(&self) -> WeakSingleThreadEventEmitter<K> {
+        WeakSingleThreadEventEmitter {
+            inner: Rc::downgrade(&self.inner),
+        }
+    }
+
+    /// Stops the emitter: later emits are dropped, cancellation tokens are
+    /// cancelled, and in-flight async handlers get until `deadline` to finish
+    /// before the rest are aborted.
+    /// Must be awaited on the thread running the spawner.
+    #[cfg(feature = "async")]
+    pub async fn shutdown(&self, deadline: Duration) -> ShutdownReport<K> {
+        self.inner.shutdown.borrow_mut().closing = true;
+        self.inner.in_flight.borrow_mut().cancel_tokens();
+
+        let sleep = self.sleep(deadline).unwrap_or_else(|| runtime::timer_sleep(deadline));
+        let drained = runtime::timeout(sleep, self.inner.tracker.wait()).await;
+        let cancelled = match drained {
+            Some(()) => Vec::new(),
+            None => self.inner.in_flight.borrow_mut().abort_all(),
         };
+        ShutdownReport {
+            cancelled,
+            rejected: self.inner.shutdown.borrow_mut().rejected,
+        }
+    }
 
-        self.listeners
-            .borrow_mut()
-            .entry(event.to_string())
-            .or_default()
-            .push(handler);
+    /// Whether an emit must be dropped because `shutdown` was called.
+    fn reject_emit(&self) -> bool {
+        self.inner.shutdown.borrow_mut().reject()
+    }
 
-        id
+    #[cfg(feature = "async")]
+    fn local_spawner(&self) -> Option<Rc<dyn LocalSpawner>> {
+        self.config()
+            .spawner
+            .clone()
+            .or_else(runtime::default_local_spawner)
+    }
+
+    /// Timer of the spawner, used for deadlines and timeouts; `None` without
+    /// a runtime.
+    #[cfg(feature = "async")]
+    pub(crate) fn sleep(&self, duration: Duration) -> Option<HandlerFuture> {
+        self.local_spawner().map(|spawner| spawner.sleep(duration))
     }
 
-    fn off(&self, event: &str, id: HandlerId) -> bool {
-        let mut listeners = self.listeners.borrow_mut();
+    fn get_id(&self) -> HandlerId {
+        let mut id = self.inner.id_counter.borrow_mut();
+        let old_value = *id;
+        *id += 1;
+        old_value
+    }
+
+    #[track_caller]
+    fn add_handler(
+        &self,
+        event: &K::Ref,
+        callback: Callback,
+        options: ListenerOptions,
+        filter: Option<Filter>,
+    ) -> Result<HandlerId, MaxListenersExceeded> {
+        let (id, warning, payloads) = {
+            let mut listeners = self.inner.listeners.borrow_mut();
+            let handlers = listeners.entry(event.to_owned()).or_default();
+            let warning = self.config().limits.check(event, handlers.len())?;
+            let id = self.get_id();
+            let index = insert_position(handlers.iter().map(|h| h.priority), &options);
+            let handler = Handler {
+                filter,
+                ..Handler::new(id, callback, options)
+            };
+            handlers.insert(index, handler);
+            // Taken with the listener in place, so a payload emitted from now
+            // on reaches it live and is not replayed as well.
+            let payloads = self
+                .inner
+                .retained
+                .borrow_mut()
+                .get_mut(event)
+                .map(Retained::payloads);
+            (id, warning, payloads)
+        };
+
+        if let Some(warning) = warning {
+            self.warn_max_listeners(&warning);
+        }
+        if let Some(payloads) = payloads {
+            self.replay(event, id, payloads);
+        }
+        self.emit_meta(K::new_listener_event(), event, id);
+        Ok(id)
+    }
+
+    #[track_caller]
+    fn add_pattern_handler(
+        &self,
+        pattern: &str,
+        callback: Callback,
+        options: ListenerOptions,
+    ) -> Result<HandlerId, MaxListenersExceeded> {
+        let (id, warning) = {
+            let mut patterns = self.inner.patterns.borrow_mut();
+            let handlers = patterns.entry(pattern, self.config().delimiter);
+            let warning = self
+                .config()
+                .limits
+                .check_pattern(pattern, handlers.len())?;
+            let id = self.get_id();
+            let index = insert_position(handlers.iter().map(|h| h.priority), &options);
+            let handler = Handler {
+                pattern: Some(pattern.to_string()),
+                ..Handler::new(id, callback, options)
+            };
+            handlers.insert(index, handler);
+            (id, warning)
+        };
+
+        if let Some(warning) = warning {
+            self.warn_max_listeners(&warning);
+        }
+        self.emit_pattern_meta(K::new_listener_event(), pattern, id);
+        Ok(id)
+    }
+
+    // Called after the borrow is released so the hook may register or remove listeners.
+    fn warn_max_listeners(&self, warning: &MaxListenersExceeded) {
+        match &self.config().limits.hook {
+            Some(hook) => hook(warning),
+            #[cfg(feature = "std")]
+            None => std::eprintln!("MaxListenersExceededWarning: {warning}"),
+            // Without std there is nowhere to print the warning.
+            #[cfg(not(feature = "std"))]
+            None => {}
+        }
+    }
+
+    fn remove_handler(&self, event: &K::Ref, id: HandlerId) -> bool {
+        let mut listeners = self.inner.listeners.borrow_mut();
 
         if let Some(handlers) = listeners.get_mut(event) {
             let len = handlers.len();
             handlers.retain(|h| h.id != id);
 
+            if len != handlers.len() {
+                return true;
+            }
+        }
+
+        let mut patterns = self.inner.patterns.borrow_mut();
+        if !patterns.is_empty()
+            && let Some(handlers) = patterns.get_mut(&K::name(event), self.config().delimiter)
+        {
+            let len = handlers.len();
+            handlers.retain(|h| h.id != id);
+
             return len != handlers.len();
         }
 
         false
     }
 
-    fn off_all(&self, event: &str) {
-        self.listeners.borrow_mut().remove(event);
+    /// Calls a newly added listener with the payloads retained for `event`.
+    fn replay(&self, event: &K::Ref, id: HandlerId, payloads: Vec<Args>) {
+        for args in payloads {
+            let handler = self
+                .inner
+                .listeners
+                .borrow()
+                .get(event)
+                .and_then(|handlers| handlers.iter().find(|h| h.id == id))
+                .map(|h| (h.callback.clone(), h.once, h.filter.clone(), h.semaphore.clone()));
+            let Some((callback, once, filter, semaphore)) = handler else {
+                return;
+            };
+            if filter.is_some_and(|filter| !filter(&args)) {
+                continue;
+            }
+
+            if once && !self.remove_handler(event, id) {
+                return;
+            }
+            let callbacks = vec![(id, callback, semaphore)];
+            self.invoke(event, args, callbacks, &mut EmitSummary::default());
+            if once {
+                self.emit_meta(K::remove_listener_event(), event, id);
+            }
+        }
+    }
+
+    fn emit_meta(&self, meta_event: Option<&K::Ref>, event: &K::Ref, id: HandlerId) {
+        if self.config().meta_events
+            && let Some(meta_event) = meta_event
+        {
+            self.dispatch(meta_event, args![event.to_owned(), id], &mut EmitSummary::default());
+        }
+    }
+
+    fn emit_pattern_meta(&self, meta_event: Option<&K::Ref>, pattern: &str, id: HandlerId) {
+        if self.config().meta_events
+            && let Some(meta_event) = meta_event
+        {
+            self.dispatch(meta_event, args![pattern.to_string(), id], &mut EmitSummary::default());
+        }
     }
 
-    fn emit(&self, event: &str, args: Rc<Vec<Arg>>) {
-        let callbacks: Vec<Callback> = self
+    /// Exact listeners followed by matching pattern listeners, ordered by
+    /// priority. Pattern listeners of equal priority run in registration order,
+    /// those registered with `prepend` first.
+    fn matching<T, F>(&self, event: &K::Ref, f: F) -> Vec<T>
+    where
+        F: Fn(&Handler) -> T,
+    {
+        let mut matched: Vec<(i32, T)> = self
+            .inner
             .listeners
             .borrow()
             .get(event)
-            .map(|handlers| handlers.iter().map(|h| h.callback.clone()).collect())
+            .map(|handlers| handlers.iter().map(|h| (h.priority, f(h))).collect())
             .unwrap_or_default();
 
-        for callback in &callbacks {
-            if let Callback::Sync(cb) = callback {
-                cb(args.clone());
+        let patterns = self.inner.patterns.borrow();
+        if patterns.is_empty() {
+            return matched.into_iter().map(|(_, item)| item).collect();
+        }
+        let mut pattern_handlers = patterns.matches(&K::name(event), self.config().delimiter);
+        pattern_handlers.sort_by_key(|h| dispatch_order(h.priority, h.prepend, h.id));
+        pattern_handlers.dedup_by_key(|h| h.id);
+
+        matched.extend(pattern_handlers.into_iter().map(|h| (h.priority, f(h))));
+        matched.sort_by_key(|(priority, _)| Reverse(*priority));
+
+        matched.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// The event errors of `event` are emitted on, if an emit of it would
+    /// reach any listener, pattern listeners included.
+    fn error_event(&self, event: &K::Ref) -> Option<&'static K::Ref> {
+        K::error_event().filter(|error_event| {
+            *error_event != event && !self.matching(error_event, |_| ()).is_empty()
+        })
+    }
+
+    /// Like `emit_error` with `NoRuntime`, but only warns when the error is
+    /// unhandled.
+    #[cfg(feature = "async")]
+    fn report_no_runtime(&self, event: &K::Ref, id: HandlerId) {
+        match self.error_event(event) {
+            Some(_) => self.emit_error(event, id, Box::new(NoRuntime)),
+            None => warn_no_runtime(&K::name(event), id),
+        }
+    }
+
+    fn emit_error(&self, event: &K::Ref, id: HandlerId, error: HandlerError) {
+        let error = ListenerError {
+            event: K::name(event).into_owned(),
+            id,
+            error,
+        };
+
+        let Some(error_event) = self.error_event(event) else {
+            panic!("unhandled 'error' event: {error}");
+        };
+
+        self.dispatch(error_event, args![error], &mut EmitSummary::default());
+    }
+
+    #[cfg(feature = "std")]
+    fn report_panic(&self, hook: &PanicHook, event: &K::Ref, id: HandlerId, message: String) {
+        hook(&ListenerPanic {
+            event: K::name(event).into_owned(),
+            id,
+            message,
+        });
+    }
+
+    /// Runs `f` inside every interceptor's `around_sync`, first one outermost.
+    fn around_sync<F>(&self, event: &K::Ref, id: HandlerId, f: F) -> Result<(), HandlerError>
+    where
+        F: FnOnce() -> Result<(), HandlerError>,
+    {
+        fn chain<K: EventKey>(
+            interceptors: &[Rc<dyn Interceptor<K>>],
+            event: &K::Ref,
+            id: HandlerId,
+            f: &mut dyn FnMut(),
+        ) {
+            match interceptors.split_first() {
+                Some((first, rest)) => {
+                    first.around_sync(event, id, &mut || chain(rest, event, id, f));
+                }
+                None => f(),
+            }
+        }
+
+        let config = self.config();
+        if config.interceptors.is_empty() {
+            return f();
+        }
+
+        let mut f = Some(f);
+        let mut result = Ok(());
+        chain(&config.interceptors, event, id, &mut || {
+            if let Some(f) = f.take() {
+                result = f();
+            }
+        });
+        result
+    }
+
+    #[cfg(feature = "async")]
+    fn around_async(&self, event: &K::Ref, id: HandlerId, fut: HandlerFuture) -> HandlerFuture {
+        self.config()
+            .interceptors
+            .iter()
+            .rev()
+            .fold(fut, |fut, interceptor| interceptor.around_async(event, id, fut))
+    }
+
+    fn call_guarded<F>(&self, event: &K::Ref, id: HandlerId, f: F) -> Result<(), HandlerError>
+    where
+        F: FnOnce() -> Result<(), HandlerError>,
+    {
+        let f = || self.around_sync(event, id, f);
+        #[cfg(feature = "std")]
+        if let Some(hook) = &self.config().panic_hook {
+            return panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+                self.report_panic(hook, event, id, panic_message(&*payload));
+                Ok(())
+            });
+        }
+
+        f()
+    }
+
+    /// In sequential mode, reserves the next place in `event`'s lane for one
+    /// emit. Reserved under a single lock so the handlers of concurrent emits
+    /// never interleave.
+    #[cfg(feature = "async")]
+    fn reserve_lane(&self, event: &K::Ref) -> Option<LaneSlot> {
+        match self.config().dispatch_modes.get(event) {
+            DispatchMode::Concurrent => None,
+            DispatchMode::Sequential => {
+                let (done, next) = oneshot::channel::<()>();
+                let previous = self.inner.lanes.borrow_mut().insert(event.to_owned(), next);
+                Some(LaneSlot::new(previous, done))
             }
         }
+    }
+
+    /// Chains `fut` after the previous handler in `lane`, so handlers run in
+    /// dispatch order and emits in emit order. Then waits for a permit from
+    /// every concurrency limit that applies and signals the returned receiver
+    /// once `fut` is about to start.
+    #[cfg(feature = "async")]
+    fn schedule<F>(
+        &self,
+        event: &K::Ref,
+        lane: Option<&mut LaneSlot>,
+        semaphore: Option<Arc<Semaphore>>,
+        fut: F,
+    ) -> (impl Future<Output = ()> + 'static, oneshot::Receiver<()>)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let (previous, guard) = lane.map(LaneSlot::next).unzip();
+        let semaphores = self.config().concurrency.semaphores(event, semaphore);
+        let (started, on_started) = oneshot::channel::<()>();
+
+        let task = async move {
+            if let Some(previous) = previous.flatten() {
+                let _ = previous.await;
+            }
+            // Dropped when `fut` finishes or panics, releasing the next handler.
+            let _guard = guard;
+            let _permits = acquire_all(semaphores).await;
+            let _ = started.send(());
+            fut.await;
+        };
+        (task, on_started)
+    }
+
+    #[cfg(feature = "async")]
+    fn guard_task(
+        &self,
+        event: &K::Ref,
+        id: HandlerId,
+        fut: HandlerFuture,
+    ) -> impl Future<Output = ()> + 'static {
+        let fut = self.around_async(event, id, fut);
+        let emitter = self.clone();
+        let event = event.to_owned();
+        async move {
+            let Some(hook) = emitter.config().panic_hook.clone() else {
+                return fut.await;
+            };
+
+            if let Err(payload) = catch_unwind(fut).await {
+                emitter.report_panic(&hook, key_ref(&event), id, panic_message(&*payload));
+                // Keep unwinding so emit_and_wait still counts the panic.
+                panic::resume_unwind(payload);
+            }
+        }
+    }
+
+    /// Removes the once listeners in `once` that are still registered, each
+    /// paired with the pattern it was registered under, or `None` for
+    /// listeners of `event` itself. Only the emit that removes a once listener
+    /// may call it, so it fires at most once even under re-entrant emits.
+    fn claim_once(
+        &self,
+        event: &K::Ref,
+        once: Vec<(HandlerId, Option<String>)>,
+    ) -> Vec<(Option<String>, HandlerId)> {
+        let mut claimed = Vec::new();
+        if once.is_empty() {
+            return claimed;
+        }
+
+        let delimiter = self.config().delimiter;
+        let mut listeners = self.inner.listeners.borrow_mut();
+        let mut patterns = self.inner.patterns.borrow_mut();
+        for (id, pattern) in once {
+            let handlers = match &pattern {
+                Some(pattern) => patterns.get_mut(pattern, delimiter),
+                None => listeners.get_mut(event),
+            };
+            if let Some(handlers) = handlers
+                && let Some(index) = handlers.iter().position(|h| h.id == id)
+            {
+                handlers.remove(index);
+                claimed.push((pattern, id));
+            }
+        }
+        claimed
+    }
+
+    fn dispatch(
+        &self,
+        event: &K::Ref,
+        args: Args,
+        summary: &mut EmitSummary,
+    ) -> Vec<Spawned> {
+        let Some((event, args)) = self.before_emit(event, args) else {
+            return Vec::new();
+        };
+        let event = event.as_ref();
+
+        if let Some(retained) = self.inner.retained.borrow_mut().get_mut(event) {
+            retained.push(args.clone());
+        }
+
+        let matched = self.matching(event, |h| {
+            let once = h.once.then(|| h.pattern.clone());
+            (h.id, once, h.callback.clone(), h.filter.clone(), h.semaphore.clone())
+        });
+        // A filtered once listener is only used up when its filter matched.
+        let mut once = Vec::new();
+        let matched: Vec<_> = matched
+            .into_iter()
+            .filter(|(id, pattern, _, filter, _)| {
+                let accepted = filter.as_ref().is_none_or(|filter| filter(&args));
+                if accepted && let Some(pattern) = pattern {
+                    once.push((*id, pattern.clone()));
+                }
+                accepted
+            })
+            .collect();
+        let removed = self.claim_once(event, once);
+        let callbacks = matched
+            .into_iter()
+            .filter(|(id, once, ..)| once.is_none() || removed.iter().any(|(_, c)| c == id))
+            .map(|(id, _, callback, _, semaphore)| (id, callback, semaphore))
+            .collect();
+        let tasks = self.invoke(event, args, callbacks, summary);
 
-        for callback in callbacks {
-            if let Callback::Async(cb) = callback {
-                let args_clone = args.clone();
-                tokio::task::spawn_local(async move {
+        for (pattern, id) in removed {
+            match pattern {
+                Some(pattern) => self.emit_pattern_meta(K::remove_listener_event(), &pattern, id),
+                None => self.emit_meta(K::remove_listener_event(), event, id),
+            }
+        }
+
+        tasks
+    }
+
+    /// Passes an emit through every interceptor; `None` if one dropped it.
+    fn before_emit<'a>(&self, event: &'a K::Ref, args: Args) -> Option<(Cow<'a, K::Ref>, Args)> {
+        let config = self.config();
+        if config.interceptors.is_empty() {
+            return Some((Cow::Borrowed(event), args));
+        }
+
+        let mut event = event.to_owned();
+        let mut args = args;
+        for interceptor in &config.interceptors {
+            if !interceptor.before_emit(&mut event, &mut args) {
+                return None;
+            }
+        }
+        Some((Cow::Owned(event), args))
+    }
+
+    /// Runs sync callbacks in order, then spawns async ones.
+    fn invoke(
+        &self,
+        event: &K::Ref,
+        args: Args,
+        callbacks: Vec<(HandlerId, Callback, Option<Arc<Semaphore>>)>,
+        summary: &mut EmitSummary,
+    ) -> Vec<Spawned> {
+        for (id, callback, _) in &callbacks {
+            let result = match callback {
+                Callback::Sync(cb) => self.call_guarded(event, *id, || {
+                    cb(args.clone());
+                    Ok(())
+                }),
+                Callback::TrySync(cb) => self.call_guarded(event, *id, || cb(args.clone())),
+                Callback::SyncPattern(cb) => self.call_guarded(event, *id, || {
+                    cb(&K::name(event), args.clone());
+                    Ok(())
+                }),
+                #[cfg(feature = "async")]
+                Callback::Async(_) | Callback::TryAsync(_) | Callback::AsyncPattern(_) => continue,
+            };
+            if let Err(error) = result {
+                self.emit_error(event, *id, error);
+            }
+            summary.sync_count += 1;
+        }
+
+        self.spawn_async(event, args, callbacks)
+    }
+
+    #[cfg(feature = "async")]
+    fn spawn_async(
+        &self,
+        event: &K::Ref,
+        args: Args,
+        callbacks: Vec<(HandlerId, Callback, Option<Arc<Semaphore>>)>,
+    ) -> Vec<Spawned> {
+        let mut tasks = Vec::new();
+        let mut spawner = None;
+        let mut lane = None;
+        for (id, callback, semaphore) in callbacks {
+            let args_clone = args.clone();
+            let fut: HandlerFuture = match callback {
+                Callback::Async(cb) => Box::pin(async move {
                     cb(args_clone).await;
-                });
+                }),
+                Callback::TryAsync(cb) => {
+                    let emitter = self.clone();
+                    let event = event.to_owned();
+                    Box::pin(async move {
+                        if let Err(error) = cb(args_clone).await {
+                            emitter.emit_error(key_ref(&event), id, error);
+                        }
+                    })
+                }
+                Callback::AsyncPattern(cb) => {
+                    let event_name = K::name(event).into_owned();
+                    Box::pin(async move {
+                        cb(&event_name, args_clone).await;
+                    })
+                }
+                Callback::Sync(_) | Callback::TrySync(_) | Callback::SyncPattern(_) => continue,
+            };
+            let Some(spawner) = spawner.get_or_insert_with(|| self.local_spawner()) else {
+                self.report_no_runtime(event, id);
+                continue;
+            };
+            let lane = lane.get_or_insert_with(|| self.reserve_lane(event));
+            let fut = self.guard_task(event, id, fut);
+            let (task, started) = self.schedule(event, lane.as_mut(), semaphore, fut);
+            let (task, handle, outcome) = abortable(task);
+            spawner.spawn_local(Box::pin(self.inner.tracker.track(task)));
+            self.inner.in_flight.borrow_mut().track(event, id, handle);
+            tasks.push(Spawned { id, outcome, started });
+        }
+
+        tasks
+    }
+
+    /// Async listeners are compiled out, so there is nothing to spawn.
+    #[cfg(not(feature = "async"))]
+    fn spawn_async(
+        &self,
+        _event: &K::Ref,
+        _args: Args,
+        _callbacks: Vec<(HandlerId, Callback, Option<Arc<Semaphore>>)>,
+    ) -> Vec<Spawned> {
+        Vec::new()
+    }
+}
+
+impl<K: EventKey> EventEmitter<K> for SingleThreadEventEmitter<K> {
+    #[track_caller]
+    fn on<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) + 'static,
+    {
+        self.on_with(event, ListenerOptions::new(), callback)
+    }
+
+    #[track_caller]
+    fn once<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) + 'static,
+    {
+        self.on_with(event, ListenerOptions::new().once(), callback)
+    }
+
+    #[track_caller]
+    fn on_with<F>(&self, event: &K::Ref, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(Args) + 'static,
+    {
+        registered(self.add_listener(event, options, callback))
+    }
+
+    #[track_caller]
+    fn add_listener<F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        callback: F,
+    ) -> Result<HandlerId, MaxListenersExceeded>
+    where
+        F: Fn(Args) + 'static,
+    {
+        self.add_handler(event, Callback::Sync(Rc::new(callback)), options, None)
+    }
+
+    #[track_caller]
+    fn on_filtered_with<P, F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        predicate: P,
+        callback: F,
+    ) -> HandlerId
+    where
+        P: Fn(&Args) -> bool + 'static,
+        F: Fn(Args) + 'static,
+    {
+        let callback = Callback::Sync(Rc::new(callback));
+        registered(self.add_handler(event, callback, options, Some(Rc::new(predicate))))
+    }
+
+    #[track_caller]
+    fn on_pattern_with<F>(&self, pattern: &str, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(&str, Args) + 'static,
+    {
+        registered(self.add_pattern_handler(
+            pattern,
+            Callback::SyncPattern(Rc::new(callback)),
+            options,
+        ))
+    }
+
+    #[track_caller]
+    fn try_on<F, E>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) -> Result<(), E> + 'static,
+        E: Into<HandlerError>,
+    {
+        let callback: TrySyncCallback = Rc::new(move |args| callback(args).map_err(Into::into));
+        registered(self.add_handler(
+            event,
+            Callback::TrySync(callback),
+            ListenerOptions::new(),
+            None,
+        ))
+    }
+
+    fn off(&self, event: &K::Ref, id: HandlerId) -> bool {
+        let removed = self.remove_handler(event, id);
+        if removed {
+            #[cfg(feature = "async")]
+            self.inner.in_flight.borrow_mut().cancel(id, self.config().cancel_policy);
+            self.emit_meta(K::remove_listener_event(), event, id);
+        }
+        removed
+    }
+
+    fn off_all(&self, event: &K::Ref) {
+        let mut removed = self.inner.listeners.borrow_mut().remove(event).unwrap_or_default();
+
+        {
+            let mut patterns = self.inner.patterns.borrow_mut();
+            if !patterns.is_empty()
+                && let Some(handlers) = patterns.get_mut(&K::name(event), self.config().delimiter)
+            {
+                removed.append(handlers);
             }
         }
 
-        let mut listeners = self.listeners.borrow_mut();
-        let handlers_opt = listeners.get_mut(event);
-        if let Some(handlers) = handlers_opt {
-            handlers.retain(|h| !h.once);
+        for handler in removed {
+            #[cfg(feature = "async")]
+            self.inner.in_flight.borrow_mut().cancel(handler.id, self.config().cancel_policy);
+            self.emit_meta(K::remove_listener_event(), event, handler.id);
         }
     }
+
+    fn emit(&self, event: &K::Ref, args: Rc<Vec<Arg>>) {
+        if self.reject_emit() {
+            return;
+        }
+        self.dispatch(event, args, &mut EmitSummary::default());
+    }
+
+    fn event_names(&self) -> Vec<K> {
+        self
+            .inner
+            .listeners
+            .borrow()
+            .iter()
+            .filter(|(_, handlers)| !handlers.is_empty())
+            .map(|(event, _)| event.clone())
+            .collect()
+    }
+
+    fn event_patterns(&self) -> Vec<String> {
+        self.inner.patterns.borrow().patterns(self.config().delimiter)
+    }
+
+    fn listeners(&self, event: &K::Ref) -> Vec<ListenerInfo> {
+        self.matching(event, Handler::info)
+    }
+
+    fn raw_listeners(&self, event: &K::Ref) -> Vec<ListenerInfo> {
+        let mut infos: Vec<ListenerInfo> = self
+            .inner
+            .listeners
+            .borrow()
+            .get(event)
+            .map(|handlers| handlers.iter().map(Handler::info).collect())
+            .unwrap_or_default();
+        let patterns = self.inner.patterns.borrow();
+        if !patterns.is_empty()
+            && let Some(handlers) = patterns.get(&K::name(event), self.config().delimiter)
+        {
+            infos.extend(handlers.iter().map(Handler::info));
+        }
+        infos
+    }
 }
 
-impl AsyncEventEmitter for SingleThreadEventEmitter {
-    fn on_async<F>(&self, event: &str, callback: F) -> HandlerId
+#[cfg(feature = "async")]
+impl<K: EventKey> AsyncEventEmitter<K> for SingleThreadEventEmitter<K> {
+    #[track_caller]
+    fn on_async<F>(&self, event: &K::Ref, callback: F) -> HandlerId
     where
         F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
     {
-        let id = self.get_id();
-        let handler = Handler {
-            id,
-            callback: Callback::Async(Rc::new(callback)),
-            once: false,
-        };
+        self.on_async_with(event, ListenerOptions::new(), callback)
+    }
 
-        self.listeners
-            .borrow_mut()
-            .entry(event.to_string())
-            .or_default()
-            .push(handler);
+    #[track_caller]
+    fn once_async<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        self.on_async_with(event, ListenerOptions::new().once(), callback)
+    }
 
-        id
+    #[track_caller]
+    fn on_async_with<F>(&self, event: &K::Ref, options: ListenerOptions, callback: F) -> HandlerId
+    where
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        registered(self.add_async_listener(event, options, callback))
     }
 
-    fn once_async<F>(&self, event: &str, callback: F) -> HandlerId
+    #[track_caller]
+    fn add_async_listener<F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        callback: F,
+    ) -> Result<HandlerId, MaxListenersExceeded>
     where
         F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
     {
-        let id = self.get_id();
-        let handler = Handler {
-            id,
-            callback: Callback::Async(Rc::new(callback)),
-            once: true,
-        };
+        self.add_handler(event, Callback::Async(Rc::new(callback)), options, None)
+    }
 
-        self.listeners
-            .borrow_mut()
-            .entry(event.to_string())
-            .or_default()
-            .push(handler);
+    #[track_caller]
+    fn on_filtered_async_with<P, F>(
+        &self,
+        event: &K::Ref,
+        options: ListenerOptions,
+        predicate: P,
+        callback: F,
+    ) -> HandlerId
+    where
+        P: Fn(&Args) -> bool + 'static,
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        let callback = Callback::Async(Rc::new(callback));
+        registered(self.add_handler(event, callback, options, Some(Rc::new(predicate))))
+    }
+
+    #[track_caller]
+    fn on_pattern_async_with<F>(
+        &self,
+        pattern: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> HandlerId
+    where
+        F: Fn(&str, Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        registered(self.add_pattern_handler(
+            pattern,
+            Callback::AsyncPattern(Rc::new(callback)),
+            options,
+        ))
+    }
 
+    #[track_caller]
+    fn try_on_async<F, E>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = Result<(), E>>>> + 'static,
+        E: Into<HandlerError> + 'static,
+    {
+        let callback: TryAsyncCallback = Rc::new(move |args| {
+            let fut = callback(args);
+            Box::pin(async move { fut.await.map_err(Into::into) })
+        });
+        registered(self.add_handler(
+            event,
+            Callback::TryAsync(callback),
+            ListenerOptions::new(),
+            None,
+        ))
+    }
+
+    #[track_caller]
+    fn on_async_cancellable<F>(&self, event: &K::Ref, callback: F) -> HandlerId
+    where
+        F: Fn(Args, CancellationToken) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        let token = CancellationToken::new();
+        let listener_token = token.clone();
+        let id = self.on_async(event, move |args| callback(args, listener_token.clone()));
+        self.inner.in_flight.borrow_mut().insert_token(id, token);
         id
     }
+
+    async fn emit_and_wait(&self, event: &K::Ref, args: Rc<Vec<Arg>>) -> EmitSummary {
+        let mut summary = EmitSummary::default();
+        if self.reject_emit() {
+            return summary;
+        }
+        let tasks = self.dispatch(event, args, &mut summary);
+
+        for Spawned { id, outcome, .. } in tasks {
+            summary.async_count += 1;
+            if outcome.await == Ok(TaskOutcome::Panicked) {
+                summary.panicked.push(id);
+            }
+        }
+
+        summary
+    }
+
+    async fn emit_async(&self, event: &K::Ref, args: Rc<Vec<Arg>>) {
+        if self.reject_emit() {
+            return;
+        }
+        let tasks = self.dispatch(event, args, &mut EmitSummary::default());
+        for Spawned { started, .. } in tasks {
+            let _ = started.await;
+        }
+    }
 }
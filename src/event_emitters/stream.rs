@@ -0,0 +1,212 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+use futures_core::Stream;
+
+use crate::event_emitters::*;
+use crate::types::*;
+
+/// Bounded queue shared between a stream and the listener feeding it.
+struct Buffer<T> {
+    items: VecDeque<T>,
+    options: StreamOptions,
+    dropped: u64,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+impl<T> Buffer<T> {
+    fn new(options: StreamOptions) -> Self {
+        Self {
+            items: VecDeque::new(),
+            options,
+            dropped: 0,
+            waker: None,
+            closed: false,
+        }
+    }
+
+    fn push(&mut self, item: T) {
+        if self.items.len() >= self.options.capacity {
+            self.dropped += 1;
+            match self.options.overflow {
+                OverflowPolicy::DropOldest => {
+                    self.items.pop_front();
+                }
+                OverflowPolicy::DropNewest => return,
+            }
+        }
+
+        self.items.push_back(item);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// No more items will come: the listener was removed or the emitter dropped.
+    fn close(&mut self) {
+        self.closed = true;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.items.pop_front() {
+            Some(item) => Poll::Ready(Some(item)),
+            None if self.closed => Poll::Ready(None),
+            None => {
+                self.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Owned by the listener feeding a buffer, so the stream ends once the
+/// listener is dropped, whether by `off_all` or together with the emitter.
+struct Feed<T>(Rc<RefCell<Buffer<T>>>);
+
+impl<T> Drop for Feed<T> {
+    fn drop(&mut self) {
+        self.0.borrow_mut().close();
+    }
+}
+
+/// Thread-safe counterpart of [`Feed`].
+struct ThreadSafeFeed<T>(Arc<Mutex<Buffer<T>>>);
+
+impl<T> Drop for ThreadSafeFeed<T> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).close();
+    }
+}
+
+/// Stream of the args of every emit of one event on a [`SingleThreadEventEmitter`].
+/// Its listener is removed when the stream is dropped, and the stream ends
+/// once the listener is gone.
+pub struct ListenerStream<K: EventKey = String> {
+    buffer: Rc<RefCell<Buffer<Args>>>,
+    subscription: Subscription<K>,
+}
+
+impl<K: EventKey> ListenerStream<K> {
+    pub fn id(&self) -> HandlerId {
+        self.subscription.id()
+    }
+
+    /// Payloads discarded so far because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.buffer.borrow().dropped
+    }
+}
+
+impl<K: EventKey> Stream for ListenerStream<K> {
+    type Item = Args;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Args>> {
+        self.buffer.borrow_mut().poll(cx)
+    }
+}
+
+impl<K: EventKey> std::fmt::Debug for ListenerStream<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ListenerStream")
+            .field("subscription", &self.subscription)
+            .field("buffered", &self.buffer.borrow().items.len())
+            .finish()
+    }
+}
+
+impl<K: EventKey> SingleThreadEventEmitter<K> {
+    /// Like Node's `events.on(emitter, event)`, with the default `StreamOptions`.
+    pub fn stream(&self, event: &K::Ref) -> ListenerStream<K> {
+        self.stream_with(event, StreamOptions::new())
+    }
+
+    pub fn stream_with(&self, event: &K::Ref, options: StreamOptions) -> ListenerStream<K> {
+        let buffer = Rc::new(RefCell::new(Buffer::new(options)));
+
+        let feed = Feed(buffer.clone());
+        let subscription = self.subscribe(event, move |args| {
+            feed.0.borrow_mut().push(args);
+        });
+
+        ListenerStream {
+            buffer,
+            subscription,
+        }
+    }
+}
+
+/// Stream of the args of every emit of one event on a [`MultiThreadEventEmitter`].
+/// Its listener is removed when the stream is dropped, and the stream ends
+/// once the listener is gone.
+pub struct ThreadSafeListenerStream<K: EventKey<Ref: Sync> + Send + Sync = String> {
+    buffer: Arc<Mutex<Buffer<ThreadSafeArgs>>>,
+    subscription: ThreadSafeSubscription<K>,
+}
+
+impl<K: EventKey<Ref: Sync> + Send + Sync> ThreadSafeListenerStream<K> {
+    pub fn id(&self) -> HandlerId {
+        self.subscription.id()
+    }
+
+    /// Payloads discarded so far because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.lock_buffer().dropped
+    }
+
+    fn lock_buffer(&self) -> std::sync::MutexGuard<'_, Buffer<ThreadSafeArgs>> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<K: EventKey<Ref: Sync> + Send + Sync> Stream for ThreadSafeListenerStream<K> {
+    type Item = ThreadSafeArgs;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ThreadSafeArgs>> {
+        self.lock_buffer().poll(cx)
+    }
+}
+
+impl<K: EventKey<Ref: Sync> + Send + Sync> std::fmt::Debug for ThreadSafeListenerStream<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ThreadSafeListenerStream")
+            .field("subscription", &self.subscription)
+            .field("buffered", &self.lock_buffer().items.len())
+            .finish()
+    }
+}
+
+impl<K: EventKey<Ref: Sync> + Send + Sync> MultiThreadEventEmitter<K> {
+    /// Like Node's `events.on(emitter, event)`, with the default `StreamOptions`.
+    pub fn stream(&self, event: &K::Ref) -> ThreadSafeListenerStream<K> {
+        self.stream_with(event, StreamOptions::new())
+    }
+
+    pub fn stream_with(
+        &self,
+        event: &K::Ref,
+        options: StreamOptions,
+    ) -> ThreadSafeListenerStream<K> {
+        let buffer = Arc::new(Mutex::new(Buffer::new(options)));
+
+        let feed = ThreadSafeFeed(buffer.clone());
+        let subscription = self.subscribe(event, move |args| {
+            feed.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(args);
+        });
+
+        ThreadSafeListenerStream {
+            buffer,
+            subscription,
+        }
+    }
+}
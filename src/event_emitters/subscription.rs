@@ -0,0 +1,212 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+#[cfg(feature = "async")]
+use core::pin::Pin;
+
+use crate::basis::key_ref;
+use crate::event_emitters::*;
+use crate::types::*;
+
+/// Removes its listener from a [`SingleThreadEventEmitter`] when dropped.
+/// Holds only a weak reference, so it never keeps the emitter alive.
+#[must_use = "dropping a Subscription removes the listener immediately"]
+pub struct Subscription<K: EventKey = String> {
+    emitter: WeakSingleThreadEventEmitter<K>,
+    event: K,
+    id: HandlerId,
+    active: bool,
+}
+
+impl<K: EventKey> Subscription<K> {
+    pub fn new(emitter: &SingleThreadEventEmitter<K>, event: &K::Ref, id: HandlerId) -> Self {
+        Self {
+            emitter: emitter.downgrade(),
+            event: event.to_owned(),
+            id,
+            active: true,
+        }
+    }
+
+    pub fn id(&self) -> HandlerId {
+        self.id
+    }
+
+    pub fn event(&self) -> &K::Ref {
+        key_ref(&self.event)
+    }
+
+    /// Removes the listener now. Returns `false` if it was already gone.
+    pub fn unsubscribe(mut self) -> bool {
+        self.remove()
+    }
+
+    /// Detaches the guard and leaves the listener registered.
+    pub fn forget(mut self) -> HandlerId {
+        self.active = false;
+        self.id
+    }
+
+    fn remove(&mut self) -> bool {
+        if !core::mem::replace(&mut self.active, false) {
+            return false;
+        }
+
+        self.emitter
+            .upgrade()
+            .is_some_and(|emitter| emitter.off(key_ref(&self.event), self.id))
+    }
+}
+
+impl<K: EventKey> Drop for Subscription<K> {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+impl<K: EventKey> core::fmt::Debug for Subscription<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Subscription")
+            .field("event", &K::name(key_ref(&self.event)))
+            .field("id", &self.id)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+impl<K: EventKey> SingleThreadEventEmitter<K> {
+    /// Wraps an existing registration in a guard.
+    pub fn subscription(&self, event: &K::Ref, id: HandlerId) -> Subscription<K> {
+        Subscription::new(self, event, id)
+    }
+
+    #[track_caller]
+    pub fn subscribe<F>(&self, event: &K::Ref, callback: F) -> Subscription<K>
+    where
+        F: Fn(Args) + 'static,
+    {
+        let id = self.on(event, callback);
+        Subscription::new(self, event, id)
+    }
+
+    #[track_caller]
+    pub fn subscribe_once<F>(&self, event: &K::Ref, callback: F) -> Subscription<K>
+    where
+        F: Fn(Args) + 'static,
+    {
+        let id = self.once(event, callback);
+        Subscription::new(self, event, id)
+    }
+
+    #[cfg(feature = "async")]
+    #[track_caller]
+    pub fn subscribe_async<F>(&self, event: &K::Ref, callback: F) -> Subscription<K>
+    where
+        F: Fn(Args) -> Pin<Box<dyn Future<Output = ()>>> + 'static,
+    {
+        let id = self.on_async(event, callback);
+        Subscription::new(self, event, id)
+    }
+}
+
+/// Removes its listener from a [`MultiThreadEventEmitter`] when dropped.
+#[cfg(feature = "std")]
+#[must_use = "dropping a ThreadSafeSubscription removes the listener immediately"]
+pub struct ThreadSafeSubscription<K: EventKey<Ref: Sync> + Send + Sync = String> {
+    emitter: MultiThreadEventEmitter<K>,
+    event: K,
+    id: HandlerId,
+    active: bool,
+}
+
+#[cfg(feature = "std")]
+impl<K: EventKey<Ref: Sync> + Send + Sync> ThreadSafeSubscription<K> {
+    pub fn new(emitter: &MultiThreadEventEmitter<K>, event: &K::Ref, id: HandlerId) -> Self {
+        Self {
+            emitter: emitter.clone(),
+            event: event.to_owned(),
+            id,
+            active: true,
+        }
+    }
+
+    pub fn id(&self) -> HandlerId {
+        self.id
+    }
+
+    pub fn event(&self) -> &K::Ref {
+        key_ref(&self.event)
+    }
+
+    /// Removes the listener now. Returns `false` if it was already gone.
+    pub fn unsubscribe(mut self) -> bool {
+        self.remove()
+    }
+
+    /// Detaches the guard and leaves the listener registered.
+    pub fn forget(mut self) -> HandlerId {
+        self.active = false;
+        self.id
+    }
+
+    fn remove(&mut self) -> bool {
+        core::mem::replace(&mut self.active, false)
+            && self.emitter.off(key_ref(&self.event), self.id)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: EventKey<Ref: Sync> + Send + Sync> Drop for ThreadSafeSubscription<K> {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: EventKey<Ref: Sync> + Send + Sync> core::fmt::Debug for ThreadSafeSubscription<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ThreadSafeSubscription")
+            .field("event", &K::name(key_ref(&self.event)))
+            .field("id", &self.id)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: EventKey<Ref: Sync> + Send + Sync> MultiThreadEventEmitter<K> {
+    /// Wraps an existing registration in a guard.
+    pub fn subscription(&self, event: &K::Ref, id: HandlerId) -> ThreadSafeSubscription<K> {
+        ThreadSafeSubscription::new(self, event, id)
+    }
+
+    #[track_caller]
+    pub fn subscribe<F>(&self, event: &K::Ref, callback: F) -> ThreadSafeSubscription<K>
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        let id = self.on(event, callback);
+        ThreadSafeSubscription::new(self, event, id)
+    }
+
+    #[track_caller]
+    pub fn subscribe_once<F>(&self, event: &K::Ref, callback: F) -> ThreadSafeSubscription<K>
+    where
+        F: Fn(ThreadSafeArgs) + Send + Sync + 'static,
+    {
+        let id = self.once(event, callback);
+        ThreadSafeSubscription::new(self, event, id)
+    }
+
+    #[cfg(feature = "async")]
+    #[track_caller]
+    pub fn subscribe_async<F>(&self, event: &K::Ref, callback: F) -> ThreadSafeSubscription<K>
+    where
+        F: Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let id = self.on_async(event, callback);
+        ThreadSafeSubscription::new(self, event, id)
+    }
+}
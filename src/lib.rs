@@ -1,6 +1,17 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod basis;
+mod topic;
+#[cfg(feature = "async")]
+mod tasks;
 pub mod event_emitters;
 pub mod types;
+pub mod typed;
 pub mod macros;
+#[cfg(feature = "async")]
+pub mod runtime;
 
-pub use event_emitters::*;
\ No newline at end of file
+pub use event_emitters::*;
+pub use typed::*;
@@ -2,7 +2,9 @@
 #[macro_export]
 macro_rules! args {
     ($($v:expr),* $(,)?) => {
-        std::rc::Rc::new(vec![$(Box::new($v) as Box<dyn std::any::Any>),*])
+        $crate::macros::__private::Rc::new($crate::macros::__private::vec![
+            $($crate::macros::__private::Box::new($v) as $crate::types::Arg),*
+        ])
     };
 }
 
@@ -10,6 +12,17 @@ macro_rules! args {
 #[macro_export]
 macro_rules! ts_args {
     ($($v:expr),* $(,)?) => {
-        std::sync::Arc::new(vec![$(Box::new($v) as Box<dyn std::any::Any + Send + Sync>),*])
+        $crate::macros::__private::Arc::new($crate::macros::__private::vec![
+            $($crate::macros::__private::Box::new($v) as $crate::types::ThreadSafeArg),*
+        ])
     };
 }
+
+/// Paths used by the macros, so they also expand in `no_std` crates.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::boxed::Box;
+    pub use alloc::rc::Rc;
+    pub use alloc::sync::Arc;
+    pub use alloc::vec;
+}
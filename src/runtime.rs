@@ -0,0 +1,300 @@
+//! Executors that async listeners are spawned on.
+//!
+//! Each backend sits behind a cargo feature: `tokio` (the default),
+//! `async-std` and `smol`. With only `async` enabled, bring your own executor
+//! by implementing [`Spawner`] / [`LocalSpawner`] or by passing a closure.
+
+use std::collections::BTreeMap;
+use std::future::{self, poll_fn};
+use std::pin::pin;
+use std::rc::Rc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, OnceLock};
+use std::task::Poll;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tokio::sync::oneshot;
+
+use crate::types::*;
+
+/// Spawns the async handlers of a `MultiThreadEventEmitter`.
+pub trait Spawner: Send + Sync + 'static {
+    fn spawn(&self, fut: ThreadSafeHandlerFuture);
+
+    /// Resolves after `duration`; used for timeouts and shutdown deadlines.
+    /// The default uses a timer thread shared by the whole process, so
+    /// override it if the executor has a timer of its own.
+    fn sleep(&self, duration: Duration) -> ThreadSafeHandlerFuture {
+        timer_sleep(duration)
+    }
+}
+
+impl<F> Spawner for F
+where
+    F: Fn(ThreadSafeHandlerFuture) + Send + Sync + 'static,
+{
+    fn spawn(&self, fut: ThreadSafeHandlerFuture) {
+        self(fut)
+    }
+}
+
+/// Spawns the async handlers of a `SingleThreadEventEmitter` on the current thread.
+pub trait LocalSpawner: 'static {
+    fn spawn_local(&self, fut: HandlerFuture);
+
+    /// Resolves after `duration`; see [`Spawner::sleep`].
+    fn sleep(&self, duration: Duration) -> HandlerFuture {
+        timer_sleep(duration)
+    }
+}
+
+impl<F> LocalSpawner for F
+where
+    F: Fn(HandlerFuture) + 'static,
+{
+    fn spawn_local(&self, fut: HandlerFuture) {
+        self(fut)
+    }
+}
+
+/// A sleep waiting on the shared timer thread.
+type TimerEntry = (Instant, oneshot::Sender<()>);
+
+/// Timer for executors without one. Every sleep is woken by a single thread
+/// started on first use, instead of a thread per sleep.
+pub(crate) fn timer_sleep(duration: Duration) -> ThreadSafeHandlerFuture {
+    static TIMER: OnceLock<mpsc::Sender<TimerEntry>> = OnceLock::new();
+
+    // A deadline past what `Instant` can hold is never reached.
+    let Some(deadline) = Instant::now().checked_add(duration) else {
+        return Box::pin(future::pending());
+    };
+    let timer = TIMER.get_or_init(|| {
+        let (timer, entries) = mpsc::channel();
+        thread::Builder::new()
+            .name("nodevent-timer".to_string())
+            .spawn(move || run_timer(entries))
+            .expect("failed to start the timer thread");
+        timer
+    });
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let _ = timer.send((deadline, tx));
+    Box::pin(async move {
+        let _ = rx.await;
+    })
+}
+
+/// Wakes each sleep once its deadline passes, earliest first.
+fn run_timer(entries: mpsc::Receiver<TimerEntry>) {
+    let mut pending: BTreeMap<(Instant, u64), oneshot::Sender<()>> = BTreeMap::new();
+    let mut seq = 0u64;
+    loop {
+        let now = Instant::now();
+        while let Some(entry) = pending.first_entry()
+            && entry.key().0 <= now
+        {
+            let _ = entry.remove().send(());
+        }
+
+        let next = match pending.first_key_value() {
+            Some(((deadline, _), _)) => entries.recv_timeout(deadline.duration_since(now)),
+            None => entries.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match next {
+            Ok((deadline, tx)) => {
+                seq += 1;
+                pending.insert((deadline, seq), tx);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+/// Runs `fut` until `sleep` resolves; `None` if it did not finish in time.
+pub(crate) async fn timeout<F, S>(sleep: S, fut: F) -> Option<F::Output>
+where
+    F: Future,
+    S: Future<Output = ()>,
+{
+    let mut fut = pin!(fut);
+    let mut sleep = pin!(sleep);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = fut.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        sleep.as_mut().poll(cx).map(|()| None)
+    })
+    .await
+}
+
+/// Spawner of the runtime the caller is on. A running tokio runtime wins,
+/// then the global async-std or smol executor.
+pub(crate) fn current_spawner() -> Option<Arc<dyn Spawner>> {
+    #[cfg(feature = "tokio")]
+    if let Some(spawner) = TokioSpawner::current() {
+        return Some(Arc::new(spawner));
+    }
+    global_spawner()
+}
+
+#[cfg(feature = "async-std")]
+fn global_spawner() -> Option<Arc<dyn Spawner>> {
+    Some(Arc::new(AsyncStdSpawner))
+}
+
+#[cfg(all(feature = "smol", not(feature = "async-std")))]
+fn global_spawner() -> Option<Arc<dyn Spawner>> {
+    Some(Arc::new(SmolSpawner))
+}
+
+#[cfg(not(any(feature = "async-std", feature = "smol")))]
+fn global_spawner() -> Option<Arc<dyn Spawner>> {
+    None
+}
+
+/// Spawner the single-thread emitter uses unless one was set. smol has no
+/// global local executor, so it needs a [`SmolLocalSpawner`].
+#[cfg(feature = "tokio")]
+pub(crate) fn default_local_spawner() -> Option<Rc<dyn LocalSpawner>> {
+    Some(Rc::new(TokioLocalSpawner))
+}
+
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) fn default_local_spawner() -> Option<Rc<dyn LocalSpawner>> {
+    Some(Rc::new(AsyncStdSpawner))
+}
+
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+pub(crate) fn default_local_spawner() -> Option<Rc<dyn LocalSpawner>> {
+    None
+}
+
+/// Spawner for `MissingRuntime::Internal`: a tokio runtime started on first
+/// use and shared by every emitter for the rest of the process.
+pub(crate) fn internal_spawner() -> Option<Arc<dyn Spawner>> {
+    #[cfg(feature = "tokio")]
+    {
+        use std::sync::OnceLock;
+
+        static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+        let runtime = RUNTIME.get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .thread_name("nodevent-runtime")
+                .enable_all()
+                .build()
+                .expect("failed to start the internal tokio runtime")
+        });
+        Some(Arc::new(TokioSpawner(runtime.handle().clone())))
+    }
+    #[cfg(not(feature = "tokio"))]
+    None
+}
+
+/// Spawns onto a tokio runtime through its `Handle`.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone)]
+pub struct TokioSpawner(pub tokio::runtime::Handle);
+
+#[cfg(feature = "tokio")]
+impl TokioSpawner {
+    /// The runtime the caller is running on, if any.
+    pub fn current() -> Option<Self> {
+        tokio::runtime::Handle::try_current().ok().map(Self)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Spawner for TokioSpawner {
+    fn spawn(&self, fut: ThreadSafeHandlerFuture) {
+        self.0.spawn(fut);
+    }
+
+    fn sleep(&self, duration: Duration) -> ThreadSafeHandlerFuture {
+        let _runtime = self.0.enter();
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Spawns with `tokio::task::spawn_local`, so emits must happen inside a `LocalSet`.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioLocalSpawner;
+
+#[cfg(feature = "tokio")]
+impl LocalSpawner for TokioLocalSpawner {
+    fn spawn_local(&self, fut: HandlerFuture) {
+        tokio::task::spawn_local(fut);
+    }
+
+    fn sleep(&self, duration: Duration) -> HandlerFuture {
+        // Created on first poll, so the timer is looked up inside the runtime.
+        Box::pin(async move { tokio::time::sleep(duration).await })
+    }
+}
+
+/// Spawns onto the global async-std executor; as a [`LocalSpawner`] it uses
+/// `async_std::task::spawn_local`.
+#[cfg(feature = "async-std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsyncStdSpawner;
+
+#[cfg(feature = "async-std")]
+impl Spawner for AsyncStdSpawner {
+    fn spawn(&self, fut: ThreadSafeHandlerFuture) {
+        async_std::task::spawn(fut);
+    }
+
+    fn sleep(&self, duration: Duration) -> ThreadSafeHandlerFuture {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl LocalSpawner for AsyncStdSpawner {
+    fn spawn_local(&self, fut: HandlerFuture) {
+        async_std::task::spawn_local(fut);
+    }
+
+    fn sleep(&self, duration: Duration) -> HandlerFuture {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// Spawns onto smol's global executor.
+#[cfg(feature = "smol")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SmolSpawner;
+
+#[cfg(feature = "smol")]
+impl Spawner for SmolSpawner {
+    fn spawn(&self, fut: ThreadSafeHandlerFuture) {
+        smol::spawn(fut).detach();
+    }
+
+    fn sleep(&self, duration: Duration) -> ThreadSafeHandlerFuture {
+        Box::pin(async move {
+            smol::Timer::after(duration).await;
+        })
+    }
+}
+
+/// Spawns onto a smol `LocalExecutor`, which the caller keeps running.
+#[cfg(feature = "smol")]
+#[derive(Clone)]
+pub struct SmolLocalSpawner(pub Rc<smol::LocalExecutor<'static>>);
+
+#[cfg(feature = "smol")]
+impl LocalSpawner for SmolLocalSpawner {
+    fn spawn_local(&self, fut: HandlerFuture) {
+        self.0.spawn(fut).detach();
+    }
+
+    fn sleep(&self, duration: Duration) -> HandlerFuture {
+        Box::pin(async move {
+            smol::Timer::after(duration).await;
+        })
+    }
+}
@@ -0,0 +1,383 @@
+//! Task bookkeeping behind async listeners: scheduling limits, aborting and
+//! draining spawned handlers, whichever executor runs them.
+
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::Poll;
+
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore, oneshot};
+
+use crate::runtime::Spawner;
+use crate::types::*;
+
+/// Polls `fut`, turning a panic in any poll into an `Err`.
+pub async fn catch_unwind<F: Future>(fut: F) -> std::thread::Result<F::Output> {
+    let mut fut = pin!(fut);
+    std::future::poll_fn(|cx| {
+        match panic::catch_unwind(AssertUnwindSafe(|| fut.as_mut().poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    })
+    .await
+}
+
+/// Reports an async listener that could not be spawned when nothing listens
+/// on `"error"`. Printed once per process instead of panicking, since missing
+/// a runtime is a setup issue rather than a failing listener.
+pub fn warn_no_runtime(event: &str, id: HandlerId) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if !WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!("NoRuntimeWarning: async listener {id} of '{event}' skipped: {NoRuntime}");
+    }
+}
+
+/// Per-emitter dispatch mode with per-event overrides.
+#[derive(Clone)]
+pub struct DispatchModes<K> {
+    pub default_mode: DispatchMode,
+    pub per_event: HashMap<K, DispatchMode>,
+}
+
+impl<K> Default for DispatchModes<K> {
+    fn default() -> Self {
+        Self {
+            default_mode: DispatchMode::default(),
+            per_event: HashMap::new(),
+        }
+    }
+}
+
+impl<K: EventKey> DispatchModes<K> {
+    pub fn get(&self, event: &K::Ref) -> DispatchMode {
+        self.per_event
+            .get(event)
+            .copied()
+            .unwrap_or(self.default_mode)
+    }
+}
+
+/// Emitter-wide and per-event limits on concurrently running async handlers.
+#[derive(Clone)]
+pub struct ConcurrencyLimits<K> {
+    pub emitter: Option<Arc<Semaphore>>,
+    pub per_event: HashMap<K, Arc<Semaphore>>,
+}
+
+impl<K> Default for ConcurrencyLimits<K> {
+    fn default() -> Self {
+        Self {
+            emitter: None,
+            per_event: HashMap::new(),
+        }
+    }
+}
+
+impl<K: EventKey> ConcurrencyLimits<K> {
+    pub fn set_emitter(&mut self, n: usize) {
+        self.emitter = semaphore(n);
+    }
+
+    pub fn set_event(&mut self, event: &K::Ref, n: usize) {
+        match semaphore(n) {
+            Some(semaphore) => self.per_event.insert(event.to_owned(), semaphore),
+            None => self.per_event.remove(event),
+        };
+    }
+
+    /// Semaphores an async handler of `event` must hold, always in the same
+    /// order so concurrent handlers cannot deadlock. The narrowest comes first
+    /// (listener, event, emitter): a handler queued behind its own listener's
+    /// limit holds no emitter-wide slot that other listeners could use.
+    pub fn semaphores(
+        &self,
+        event: &K::Ref,
+        listener: Option<Arc<Semaphore>>,
+    ) -> Vec<Arc<Semaphore>> {
+        listener
+            .into_iter()
+            .chain(self.per_event.get(event).cloned())
+            .chain(self.emitter.clone())
+            .collect()
+    }
+}
+
+/// Semaphore enforcing a concurrency limit of `n`. Like max listeners, a
+/// limit of 0 means unlimited.
+pub fn semaphore(n: usize) -> Option<Arc<Semaphore>> {
+    (n > 0).then(|| Arc::new(Semaphore::new(n)))
+}
+
+pub async fn acquire_all(semaphores: Vec<Arc<Semaphore>>) -> Vec<OwnedSemaphorePermit> {
+    let mut permits = Vec::with_capacity(semaphores.len());
+    for semaphore in semaphores {
+        if let Ok(permit) = semaphore.acquire_owned().await {
+            permits.push(permit);
+        }
+    }
+    permits
+}
+
+/// One emit's place in the sequential lane of its event. The emit's handlers
+/// run one after another behind the previous emit's, and the lane moves on to
+/// the next emit once the last of them has finished.
+pub struct LaneSlot {
+    previous: Option<oneshot::Receiver<()>>,
+    done: Arc<oneshot::Sender<()>>,
+}
+
+/// Held by a handler while it runs; dropping it releases the next handler.
+pub struct LaneGuard {
+    _handler: oneshot::Sender<()>,
+    _emit: Arc<oneshot::Sender<()>>,
+}
+
+impl LaneSlot {
+    /// `previous` resolves when the emit before this one is done; `done` is
+    /// dropped once every handler of this emit has finished.
+    pub fn new(previous: Option<oneshot::Receiver<()>>, done: oneshot::Sender<()>) -> Self {
+        Self {
+            previous,
+            done: Arc::new(done),
+        }
+    }
+
+    /// Link for the next handler of the emit: what it waits for before
+    /// starting, and the guard it holds while running.
+    pub fn next(&mut self) -> (Option<oneshot::Receiver<()>>, LaneGuard) {
+        let (handler, next) = oneshot::channel();
+        let previous = self.previous.replace(next);
+        let guard = LaneGuard {
+            _handler: handler,
+            _emit: self.done.clone(),
+        };
+        (previous, guard)
+    }
+}
+
+/// An async handler spawned by an emit. `started` resolves once the handler
+/// got past sequential ordering and concurrency limits.
+pub struct Spawned {
+    pub id: HandlerId,
+    pub outcome: oneshot::Receiver<TaskOutcome>,
+    pub started: oneshot::Receiver<()>,
+}
+
+/// How a spawned handler task ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskOutcome {
+    Completed,
+    Panicked,
+    Aborted,
+}
+
+/// Aborts a spawned handler task, whichever executor runs it.
+#[derive(Clone)]
+pub struct TaskHandle {
+    abort: CancellationToken,
+    finished: Arc<AtomicBool>,
+}
+
+impl TaskHandle {
+    /// Stops the task at its next await point.
+    pub fn abort(&self) {
+        self.abort.cancel();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+}
+
+/// Sets the finished flag however the task ends, including when the executor
+/// drops it without running it to completion.
+struct Finished(Arc<AtomicBool>);
+
+impl Drop for Finished {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+/// Wraps `fut` so it can be aborted through the returned handle, catching a
+/// panic and reporting how it ended on the returned receiver.
+pub fn abortable<F>(
+    fut: F,
+) -> (impl Future<Output = ()>, TaskHandle, oneshot::Receiver<TaskOutcome>)
+where
+    F: Future<Output = ()>,
+{
+    let handle = TaskHandle {
+        abort: CancellationToken::new(),
+        finished: Arc::new(AtomicBool::new(false)),
+    };
+    let (done, outcome) = oneshot::channel();
+    let abort = handle.abort.clone();
+    let finished = Finished(handle.finished.clone());
+
+    let task = async move {
+        let _finished = finished;
+        let outcome = match abort.run_until_cancelled(catch_unwind(fut)).await {
+            Some(Ok(())) => TaskOutcome::Completed,
+            Some(Err(_)) => TaskOutcome::Panicked,
+            None => TaskOutcome::Aborted,
+        };
+        let _ = done.send(outcome);
+    };
+    (task, handle, outcome)
+}
+
+/// Counts spawned handler tasks that have not ended yet, so `shutdown` can
+/// wait for them to drain.
+#[derive(Clone, Default)]
+pub struct TaskCounter(Arc<Counter>);
+
+#[derive(Default)]
+struct Counter {
+    running: AtomicUsize,
+    idle: Notify,
+}
+
+/// Decrements the counter when its task ends or is dropped unfinished.
+struct Running(Arc<Counter>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        if self.0.running.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl TaskCounter {
+    /// Counts `fut` as running from now until it ends or is dropped.
+    pub fn track<F: Future>(&self, fut: F) -> impl Future<Output = F::Output> + use<F> {
+        self.0.running.fetch_add(1, Ordering::AcqRel);
+        let running = Running(self.0.clone());
+        async move {
+            let _running = running;
+            fut.await
+        }
+    }
+
+    /// Resolves once no tracked task is running.
+    pub async fn wait(&self) {
+        loop {
+            // Registered before the check so a task ending in between still wakes us.
+            let idle = self.0.idle.notified();
+            if self.0.running.load(Ordering::Acquire) == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+}
+
+/// Async tasks each listener still has running, and the cancellation tokens
+/// of `on_async_cancellable` listeners.
+pub struct InFlight<K> {
+    tasks: HashMap<HandlerId, Vec<(K, TaskHandle)>>,
+    tokens: HashMap<HandlerId, CancellationToken>,
+    tracked: usize,
+    prune_at: usize,
+}
+
+impl<K> Default for InFlight<K> {
+    fn default() -> Self {
+        Self {
+            tasks: HashMap::new(),
+            tokens: HashMap::new(),
+            tracked: 0,
+            prune_at: 0,
+        }
+    }
+}
+
+impl<K: EventKey> InFlight<K> {
+    pub fn track(&mut self, event: &K::Ref, id: HandlerId, task: TaskHandle) {
+        self.tasks.entry(id).or_default().push((event.to_owned(), task));
+        self.tracked += 1;
+
+        // Finished tasks are dropped in batches, so tracking stays amortized O(1).
+        if self.tracked > self.prune_at {
+            self.tasks.retain(|_, tasks| {
+                tasks.retain(|(_, task)| !task.is_finished());
+                !tasks.is_empty()
+            });
+            self.tracked = self.tasks.values().map(Vec::len).sum();
+            self.prune_at = (self.tracked * 2).max(64);
+        }
+    }
+
+    pub fn insert_token(&mut self, id: HandlerId, token: CancellationToken) {
+        self.tokens.insert(id, token);
+    }
+
+    /// Cancels the token of listener `id` if it has one; otherwise, under
+    /// `CancelPolicy::Abort`, aborts its unfinished tasks. Tasks left running
+    /// stay tracked, so `abort_all` still covers them at shutdown.
+    pub fn cancel(&mut self, id: HandlerId, policy: CancelPolicy) {
+        if let Some(token) = self.tokens.remove(&id) {
+            token.cancel();
+        } else if policy == CancelPolicy::Abort
+            && let Some(tasks) = self.tasks.remove(&id)
+        {
+            self.tracked -= tasks.len();
+            for (_, task) in tasks {
+                task.abort();
+            }
+        }
+    }
+
+    /// Cancels the token of every listener, asking its tasks to wind down.
+    pub fn cancel_tokens(&mut self) {
+        for (_, token) in self.tokens.drain() {
+            token.cancel();
+        }
+    }
+
+    /// Aborts every unfinished task and returns them.
+    pub fn abort_all(&mut self) -> Vec<CancelledTask<K>> {
+        self.tracked = 0;
+        let mut cancelled: Vec<CancelledTask<K>> = self
+            .tasks
+            .drain()
+            .flat_map(|(id, tasks)| tasks.into_iter().map(move |task| (id, task)))
+            .filter(|(_, (_, task))| !task.is_finished())
+            .map(|(id, (event, task))| {
+                task.abort();
+                CancelledTask { event, id }
+            })
+            .collect();
+        cancelled.sort_by_key(|task| task.id);
+        cancelled
+    }
+}
+
+/// An async handler of the multi-thread emitter waiting for a runtime.
+pub struct QueuedHandler<K> {
+    pub event: K,
+    pub id: HandlerId,
+    pub semaphore: Option<Arc<Semaphore>>,
+    pub fut: ThreadSafeHandlerFuture,
+}
+
+/// Executor the multi-thread emitter spawns async handlers on, shared by all
+/// clones so `attach_spawner` reaches every one of them.
+pub struct RuntimeState<K> {
+    pub spawner: Option<Arc<dyn Spawner>>,
+    pub queued: Vec<QueuedHandler<K>>,
+}
+
+impl<K> Default for RuntimeState<K> {
+    fn default() -> Self {
+        Self {
+            spawner: None,
+            queued: Vec::new(),
+        }
+    }
+}
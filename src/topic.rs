@@ -0,0 +1,156 @@
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use hashbrown::HashMap;
+
+/// Pattern segments that match exactly one topic segment.
+pub const SINGLE_WILDCARDS: [&str; 2] = ["*", "+"];
+/// Pattern segments that match zero or more topic segments.
+pub const MULTI_WILDCARDS: [&str; 2] = ["#", "**"];
+
+/// Pattern subscriptions stored by segment, so matching a topic only walks
+/// the branches that can match instead of testing every pattern.
+pub struct TopicTrie<H> {
+    root: Node<H>,
+}
+
+struct Node<H> {
+    literal: HashMap<String, Node<H>>,
+    single: Option<Box<Node<H>>>,
+    multi: Option<Box<Node<H>>>,
+    handlers: Vec<H>,
+}
+
+impl<H> Default for Node<H> {
+    fn default() -> Self {
+        Self {
+            literal: HashMap::new(),
+            single: None,
+            multi: None,
+            handlers: Vec::new(),
+        }
+    }
+}
+
+impl<H> Default for TopicTrie<H> {
+    fn default() -> Self {
+        Self {
+            root: Node::default(),
+        }
+    }
+}
+
+impl<H> TopicTrie<H> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handlers registered for `pattern`, creating the node if needed.
+    pub fn entry(&mut self, pattern: &str, delimiter: char) -> &mut Vec<H> {
+        let mut node = &mut self.root;
+        for segment in pattern.split(delimiter) {
+            node = if SINGLE_WILDCARDS.contains(&segment) {
+                node.single.get_or_insert_with(Default::default)
+            } else if MULTI_WILDCARDS.contains(&segment) {
+                node.multi.get_or_insert_with(Default::default)
+            } else {
+                node.literal.entry(segment.to_string()).or_default()
+            };
+        }
+        &mut node.handlers
+    }
+
+    pub fn get(&self, pattern: &str, delimiter: char) -> Option<&Vec<H>> {
+        let mut node = &self.root;
+        for segment in pattern.split(delimiter) {
+            node = if SINGLE_WILDCARDS.contains(&segment) {
+                node.single.as_deref()?
+            } else if MULTI_WILDCARDS.contains(&segment) {
+                node.multi.as_deref()?
+            } else {
+                node.literal.get(segment)?
+            };
+        }
+        Some(&node.handlers)
+    }
+
+    pub fn get_mut(&mut self, pattern: &str, delimiter: char) -> Option<&mut Vec<H>> {
+        let mut node = &mut self.root;
+        for segment in pattern.split(delimiter) {
+            node = if SINGLE_WILDCARDS.contains(&segment) {
+                node.single.as_deref_mut()?
+            } else if MULTI_WILDCARDS.contains(&segment) {
+                node.multi.as_deref_mut()?
+            } else {
+                node.literal.get_mut(segment)?
+            };
+        }
+        Some(&mut node.handlers)
+    }
+
+    /// Whether no pattern was ever registered, so matching can be skipped.
+    pub fn is_empty(&self) -> bool {
+        let root = &self.root;
+        root.handlers.is_empty()
+            && root.literal.is_empty()
+            && root.single.is_none()
+            && root.multi.is_none()
+    }
+
+    /// Collects the handlers of every pattern matching `topic`.
+    pub fn matches<'a>(&'a self, topic: &str, delimiter: char) -> Vec<&'a H> {
+        let segments: Vec<&str> = topic.split(delimiter).collect();
+        let mut out = Vec::new();
+        self.root.collect(&segments, &mut out);
+        out
+    }
+
+    /// Patterns that have at least one handler, written with `*` and `#`.
+    pub fn patterns(&self, delimiter: char) -> Vec<String> {
+        let mut out = Vec::new();
+        self.root.patterns(&mut Vec::new(), delimiter, &mut out);
+        out
+    }
+}
+
+impl<H> Node<H> {
+    fn collect<'a>(&'a self, segments: &[&str], out: &mut Vec<&'a H>) {
+        if let Some(multi) = &self.multi {
+            for skip in 0..=segments.len() {
+                multi.collect(&segments[skip..], out);
+            }
+        }
+
+        let Some((first, rest)) = segments.split_first() else {
+            out.extend(self.handlers.iter());
+            return;
+        };
+
+        if let Some(node) = self.literal.get(*first) {
+            node.collect(rest, out);
+        }
+        if let Some(single) = &self.single {
+            single.collect(rest, out);
+        }
+    }
+
+    fn patterns<'a>(&'a self, path: &mut Vec<&'a str>, delimiter: char, out: &mut Vec<String>) {
+        if !self.handlers.is_empty() && !path.is_empty() {
+            out.push(path.join(&delimiter.to_string()));
+        }
+
+        let mut literals: Vec<_> = self.literal.iter().collect();
+        literals.sort_by_key(|(segment, _)| *segment);
+        let children = literals
+            .into_iter()
+            .map(|(segment, node)| (segment.as_str(), node))
+            .chain(self.single.as_deref().map(|node| (SINGLE_WILDCARDS[0], node)))
+            .chain(self.multi.as_deref().map(|node| (MULTI_WILDCARDS[0], node)));
+        for (segment, node) in children {
+            path.push(segment);
+            node.patterns(path, delimiter, out);
+            path.pop();
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+use alloc::vec;
+use core::marker::PhantomData;
+
+use crate::event_emitters::*;
+use crate::types::*;
+
+/// An event key that carries its payload type.
+///
+/// const ORDER_PLACED: Event<OrderPlaced> = Event::new("order_placed");
+pub struct Event<T> {
+    name: &'static str,
+    _payload: PhantomData<fn(T)>,
+}
+
+impl<T> Event<T> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _payload: PhantomData,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T> Clone for Event<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Event<T> {}
+
+impl<T> core::fmt::Debug for Event<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Event").field(&self.name).finish()
+    }
+}
+
+/// Typed layer over [`EventEmitter`]. The payload travels as the first
+/// argument, so dynamic listeners on the same event still see it.
+pub trait TypedEventEmitter {
+    fn on_typed<T, F>(&self, event: &Event<T>, callback: F) -> HandlerId
+    where
+        T: 'static,
+        F: Fn(&T) + 'static;
+
+    fn once_typed<T, F>(&self, event: &Event<T>, callback: F) -> HandlerId
+    where
+        T: 'static,
+        F: Fn(&T) + 'static;
+
+    fn emit_typed<T>(&self, event: &Event<T>, payload: T)
+    where
+        T: 'static;
+}
+
+impl<E: EventEmitter> TypedEventEmitter for E {
+    #[track_caller]
+    fn on_typed<T, F>(&self, event: &Event<T>, callback: F) -> HandlerId
+    where
+        T: 'static,
+        F: Fn(&T) + 'static,
+    {
+        self.on(event.name(), move |args| {
+            if let Some(payload) = args.first().and_then(|a| a.downcast_ref::<T>()) {
+                callback(payload);
+            }
+        })
+    }
+
+    #[track_caller]
+    fn once_typed<T, F>(&self, event: &Event<T>, callback: F) -> HandlerId
+    where
+        T: 'static,
+        F: Fn(&T) + 'static,
+    {
+        // Filtered, so an emit with another payload type does not use it up.
+        self.once_filtered(
+            event.name(),
+            |args| args.first().is_some_and(|a| a.is::<T>()),
+            move |args| {
+                if let Some(payload) = args.first().and_then(|a| a.downcast_ref::<T>()) {
+                    callback(payload);
+                }
+            },
+        )
+    }
+
+    fn emit_typed<T>(&self, event: &Event<T>, payload: T)
+    where
+        T: 'static,
+    {
+        self.emit(event.name(), Rc::new(vec![Box::new(payload) as Arg]));
+    }
+}
+
+/// Typed layer over [`ThreadSafeEventEmitter`].
+#[cfg(feature = "std")]
+pub trait ThreadSafeTypedEventEmitter {
+    fn on_typed<T, F>(&self, event: &Event<T>, callback: F) -> HandlerId
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) + Send + Sync + 'static;
+
+    fn once_typed<T, F>(&self, event: &Event<T>, callback: F) -> HandlerId
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) + Send + Sync + 'static;
+
+    fn emit_typed<T>(&self, event: &Event<T>, payload: T)
+    where
+        T: Send + Sync + 'static;
+}
+
+#[cfg(feature = "std")]
+impl<E: ThreadSafeEventEmitter> ThreadSafeTypedEventEmitter for E {
+    #[track_caller]
+    fn on_typed<T, F>(&self, event: &Event<T>, callback: F) -> HandlerId
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        self.on(event.name(), move |args| {
+            if let Some(payload) = args.first().and_then(|a| a.downcast_ref::<T>()) {
+                callback(payload);
+            }
+        })
+    }
+
+    #[track_caller]
+    fn once_typed<T, F>(&self, event: &Event<T>, callback: F) -> HandlerId
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        // Filtered, so an emit with another payload type does not use it up.
+        self.once_filtered(
+            event.name(),
+            |args| args.first().is_some_and(|a| a.is::<T>()),
+            move |args| {
+                if let Some(payload) = args.first().and_then(|a| a.downcast_ref::<T>()) {
+                    callback(payload);
+                }
+            },
+        )
+    }
+
+    fn emit_typed<T>(&self, event: &Event<T>, payload: T)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.emit(
+            event.name(),
+            Arc::new(vec![Box::new(payload) as ThreadSafeArg]),
+        );
+    }
+}
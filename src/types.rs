@@ -1,4 +1,9 @@
-use std::{any::Any, pin::Pin, rc::Rc, sync::Arc};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::{boxed::Box, format, rc::Rc, string::String, sync::Arc, vec::Vec};
+use core::{any::Any, borrow::Borrow, error::Error, fmt, hash::Hash, panic::Location, pin::Pin};
+
+#[cfg(feature = "async")]
+pub use tokio_util::sync::CancellationToken;
 
 pub type HandlerId = u64;
 
@@ -13,3 +18,457 @@ pub type SyncThreadSafeCallback = Arc<dyn Fn(ThreadSafeArgs) + Send + Sync>;
 pub type AsyncCallback = Rc<dyn Fn(Args) -> Pin<Box<dyn Future<Output = ()>>>>;
 pub type AsyncThreadSafeCallback =
     Arc<dyn Fn(ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+pub type SyncPatternCallback = Rc<dyn Fn(&str, Args)>;
+pub type SyncPatternThreadSafeCallback = Arc<dyn Fn(&str, ThreadSafeArgs) + Send + Sync>;
+pub type AsyncPatternCallback = Rc<dyn Fn(&str, Args) -> Pin<Box<dyn Future<Output = ()>>>>;
+pub type AsyncPatternThreadSafeCallback = Arc<
+    dyn Fn(&str, ThreadSafeArgs) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync,
+>;
+
+/// Predicate checked before a filtered listener is called.
+pub type Filter = Rc<dyn Fn(&Args) -> bool>;
+pub type ThreadSafeFilter = Arc<dyn Fn(&ThreadSafeArgs) -> bool + Send + Sync>;
+
+pub type HandlerError = Box<dyn Error>;
+pub type ThreadSafeHandlerError = Box<dyn Error + Send + Sync>;
+
+pub type TrySyncCallback = Rc<dyn Fn(Args) -> Result<(), HandlerError>>;
+pub type TrySyncThreadSafeCallback =
+    Arc<dyn Fn(ThreadSafeArgs) -> Result<(), ThreadSafeHandlerError> + Send + Sync>;
+pub type TryAsyncCallback =
+    Rc<dyn Fn(Args) -> Pin<Box<dyn Future<Output = Result<(), HandlerError>>>>>;
+pub type TryAsyncThreadSafeFuture =
+    Pin<Box<dyn Future<Output = Result<(), ThreadSafeHandlerError>> + Send>>;
+pub type TryAsyncThreadSafeCallback =
+    Arc<dyn Fn(ThreadSafeArgs) -> TryAsyncThreadSafeFuture + Send + Sync>;
+
+/// Type events are keyed by. Emitters default to `String` names; key them by
+/// your own enum or an interned ID instead, so a misspelt event is rejected by
+/// the compiler and lookups hash a small key instead of a string.
+/// The integer types implement it with `Ref = Self`.
+pub trait EventKey: Hash + Eq + Clone + Borrow<Self::Ref> + 'static {
+    /// Borrowed form taken by `on`, `emit` and the other methods: `str` for
+    /// `String`, usually `Self` otherwise.
+    type Ref: ?Sized + Hash + Eq + fmt::Debug + ToOwned<Owned = Self>;
+
+    /// Name used in `ListenerError`, `ListenerPanic` and `MaxListenersExceeded`,
+    /// and matched against pattern subscriptions. The `Debug` output by default.
+    fn name(event: &Self::Ref) -> Cow<'_, str> {
+        Cow::Owned(format!("{event:?}"))
+    }
+
+    /// Event that receives handler errors. Without one, an error panics out
+    /// of `emit`.
+    fn error_event() -> Option<&'static Self::Ref> {
+        None
+    }
+
+    /// Event emitted after a listener is added when meta-events are enabled.
+    fn new_listener_event() -> Option<&'static Self::Ref> {
+        None
+    }
+
+    /// Event emitted after a listener is removed when meta-events are enabled.
+    fn remove_listener_event() -> Option<&'static Self::Ref> {
+        None
+    }
+}
+
+impl EventKey for String {
+    type Ref = str;
+
+    fn name(event: &str) -> Cow<'_, str> {
+        Cow::Borrowed(event)
+    }
+
+    fn error_event() -> Option<&'static str> {
+        Some(ERROR_EVENT)
+    }
+
+    fn new_listener_event() -> Option<&'static str> {
+        Some(NEW_LISTENER_EVENT)
+    }
+
+    fn remove_listener_event() -> Option<&'static str> {
+        Some(REMOVE_LISTENER_EVENT)
+    }
+}
+
+macro_rules! impl_event_key {
+    ($($ty:ty),*) => {
+        $(
+            impl EventKey for $ty {
+                type Ref = $ty;
+            }
+        )*
+    };
+}
+
+// Integer keys for interned IDs, named by their decimal form.
+impl_event_key!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Reserved event that receives errors returned by `try_on` / `try_on_async` handlers.
+pub const ERROR_EVENT: &str = "error";
+
+/// Emitted after a listener is added when meta-events are enabled.
+/// `args[0]` is the event key (`K`), or the pattern as a `String`, and
+/// `args[1]` the `HandlerId`.
+pub const NEW_LISTENER_EVENT: &str = "newListener";
+
+/// Emitted after a listener is removed by `off`, `off_all` or because a once
+/// listener fired. Carries the same payload as `"newListener"`.
+pub const REMOVE_LISTENER_EVENT: &str = "removeListener";
+
+/// Payload of the `"error"` event: `args[0]` is a `ListenerError<HandlerError>` on the
+/// single-thread emitter and a `ListenerError<ThreadSafeHandlerError>` on the multi-thread one.
+#[derive(Debug)]
+pub struct ListenerError<E> {
+    pub event: String,
+    pub id: HandlerId,
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for ListenerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "listener {} for event '{}' failed: {}",
+            self.id, self.event, self.error
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmitSummary {
+    pub sync_count: usize,
+    pub async_count: usize,
+    /// Async handlers whose task panicked.
+    pub panicked: Vec<HandlerId>,
+}
+
+/// An async handler task that `shutdown` aborted at the deadline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CancelledTask<K = String> {
+    pub event: K,
+    pub id: HandlerId,
+}
+
+/// What `shutdown` left behind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownReport<K = String> {
+    /// Handler tasks still running at the deadline, now aborted.
+    pub cancelled: Vec<CancelledTask<K>>,
+    /// Emits dropped because the emitter was shutting down.
+    pub rejected: usize,
+}
+
+impl<K> Default for ShutdownReport<K> {
+    fn default() -> Self {
+        Self {
+            cancelled: Vec::new(),
+            rejected: 0,
+        }
+    }
+}
+
+/// Reported to the panic hook when an isolated listener panics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenerPanic {
+    pub event: String,
+    pub id: HandlerId,
+    pub message: String,
+}
+
+pub type PanicHook = Rc<dyn Fn(&ListenerPanic)>;
+pub type ThreadSafePanicHook = Arc<dyn Fn(&ListenerPanic) + Send + Sync>;
+
+/// Registration options. Listeners run by descending `priority`; within the
+/// same priority they run in registration order, or first when `prepend` is set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListenerOptions {
+    pub once: bool,
+    pub prepend: bool,
+    pub priority: i32,
+    /// Free-form name shown by `listeners` / `raw_listeners`.
+    pub label: Option<String>,
+    /// Maximum number of this listener's async invocations running at once;
+    /// 0 means unlimited.
+    pub max_concurrency: Option<usize>,
+}
+
+impl ListenerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+
+    pub fn prepend(mut self) -> Self {
+        self.prepend = true;
+        self
+    }
+
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn max_concurrency(mut self, limit: usize) -> Self {
+        self.max_concurrency = Some(limit);
+        self
+    }
+}
+
+/// Why a `request` on the multi-thread emitter produced no reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestError {
+    /// Every responder finished without a reply of the requested type.
+    NoResponder,
+    Timeout,
+    /// No runtime was available to time the request.
+    NoRuntime,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::NoResponder => write!(f, "no responder replied to the request"),
+            RequestError::Timeout => write!(f, "timed out waiting for a reply"),
+            RequestError::NoRuntime => write!(f, "no async runtime available to time the request"),
+        }
+    }
+}
+
+impl Error for RequestError {}
+
+/// Which payloads of an event are kept and replayed to listeners added later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retention {
+    /// Only the most recent payload.
+    Last,
+    /// The `n` most recent payloads.
+    LastN(usize),
+    /// Every payload emitted within the window. Needs the `std` feature for
+    /// its clock.
+    #[cfg(feature = "std")]
+    Window(core::time::Duration),
+}
+
+/// Why a `next` future resolved without a payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextError {
+    /// The hidden listener was removed, e.g. by `off_all`, before the event fired.
+    Removed,
+    Timeout,
+    /// No runtime was available to time `next_timeout`.
+    NoRuntime,
+}
+
+impl fmt::Display for NextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NextError::Removed => write!(f, "listener removed before the event was emitted"),
+            NextError::Timeout => write!(f, "timed out waiting for the event"),
+            NextError::NoRuntime => write!(f, "no async runtime available to time the wait"),
+        }
+    }
+}
+
+impl Error for NextError {}
+
+/// How async handlers of an event are run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DispatchMode {
+    /// Every async handler is spawned at once and runs concurrently.
+    #[default]
+    Concurrent,
+    /// Async handlers of one emit run one after another in dispatch order,
+    /// and an emit's handlers start only after those of the previous emit of
+    /// the same event have finished.
+    Sequential,
+}
+
+/// What `off` / `off_all` do with async handler tasks that are still
+/// running for the removed listener. Listeners registered with
+/// `on_async_cancellable` are always stopped through their token instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CancelPolicy {
+    /// Running tasks are left to finish on their own.
+    #[default]
+    Detach,
+    /// Running and queued tasks are aborted at their next await point.
+    Abort,
+}
+
+/// What the multi-thread emitter does with async listeners when it has no
+/// attached spawner and no runtime of an enabled backend is available.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingRuntime {
+    /// Reports a [`NoRuntime`] error for each listener on the `"error"` event.
+    /// When nothing listens to `"error"`, the listener is skipped and a
+    /// warning is printed to stderr once per process; the emit does not panic.
+    #[default]
+    Error,
+    /// Panics out of `emit`.
+    Panic,
+    /// Keeps the handlers until `attach_spawner` is called, then spawns them.
+    Queue,
+    /// Runs them on a tokio runtime started by the crate on first use. Needs
+    /// the `tokio` feature and behaves like `Error` without it.
+    Internal,
+}
+
+/// Reported under `MissingRuntime::Error`, or by a single-thread emitter
+/// without a spawner, for an async listener that could not be spawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoRuntime;
+
+impl fmt::Display for NoRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no async runtime available to run an async listener")
+    }
+}
+
+impl Error for NoRuntime {}
+
+/// What a listener stream does with an emit when its buffer is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discard the oldest buffered payload to make room.
+    #[default]
+    DropOldest,
+    /// Discard the incoming payload.
+    DropNewest,
+}
+
+/// Options for `stream_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamOptions {
+    pub capacity: usize,
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+impl StreamOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of payloads buffered before the overflow policy applies, at least 1.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub fn overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListenerKind {
+    Sync,
+    Async,
+}
+
+/// Snapshot of a registered listener, returned by `listeners` / `raw_listeners`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenerInfo {
+    pub id: HandlerId,
+    pub once: bool,
+    pub kind: ListenerKind,
+    pub priority: i32,
+    pub label: Option<String>,
+}
+
+/// Raised when a registration takes an event over its listener limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxListenersExceeded {
+    pub event: String,
+    /// Listener count including the one being registered.
+    pub count: usize,
+    pub limit: usize,
+    /// Where the listener was registered.
+    pub location: &'static Location<'static>,
+}
+
+impl fmt::Display for MaxListenersExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "possible listener leak: {} listeners added to '{}' (limit {}) at {}",
+            self.count, self.event, self.limit, self.location
+        )
+    }
+}
+
+impl Error for MaxListenersExceeded {}
+
+pub type MaxListenersHook = Rc<dyn Fn(&MaxListenersExceeded)>;
+pub type ThreadSafeMaxListenersHook = Arc<dyn Fn(&MaxListenersExceeded) + Send + Sync>;
+
+pub type HandlerFuture = Pin<Box<dyn Future<Output = ()>>>;
+pub type ThreadSafeHandlerFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Sees every emit of a `SingleThreadEventEmitter` before dispatch and wraps
+/// each handler call. Interceptors run in registration order, so the first one
+/// registered is the outermost wrapper.
+pub trait Interceptor<K: EventKey = String> {
+    /// Inspects or rewrites the event and args. Returning `false` drops the emit.
+    fn before_emit(&self, _event: &mut K, _args: &mut Args) -> bool {
+        true
+    }
+
+    /// Wraps a sync handler call. `next` runs the inner interceptors and the
+    /// handler; not calling it skips the handler.
+    fn around_sync(&self, _event: &K::Ref, _id: HandlerId, next: &mut dyn FnMut()) {
+        next()
+    }
+
+    /// Wraps the future of an async handler before it is spawned.
+    fn around_async(&self, _event: &K::Ref, _id: HandlerId, next: HandlerFuture) -> HandlerFuture {
+        next
+    }
+}
+
+/// [`Interceptor`] for the `MultiThreadEventEmitter`.
+pub trait ThreadSafeInterceptor<K: EventKey = String>: Send + Sync {
+    /// Inspects or rewrites the event and args. Returning `false` drops the emit.
+    fn before_emit(&self, _event: &mut K, _args: &mut ThreadSafeArgs) -> bool {
+        true
+    }
+
+    /// Wraps a sync handler call. `next` runs the inner interceptors and the
+    /// handler; not calling it skips the handler.
+    fn around_sync(&self, _event: &K::Ref, _id: HandlerId, next: &mut dyn FnMut()) {
+        next()
+    }
+
+    /// Wraps the future of an async handler before it is spawned.
+    fn around_async(
+        &self,
+        _event: &K::Ref,
+        _id: HandlerId,
+        next: ThreadSafeHandlerFuture,
+    ) -> ThreadSafeHandlerFuture {
+        next
+    }
+}
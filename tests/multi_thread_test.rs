@@ -1,4 +1,5 @@
 use nodevent::*;
+use nodevent::types::*;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -126,6 +127,7 @@ fn test_emit_with_multiple_args_types() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn test_no_panic_when_event_not_found() {
     let emitter = MultiThreadEventEmitter::new();
     emitter.emit("non_existent_event", Arc::new(vec![]));
@@ -133,9 +135,999 @@ fn test_no_panic_when_event_not_found() {
     assert!(true);
 }
 
+#[derive(Debug, Clone, PartialEq)]
+struct Temperature(f64);
+
+const TEMPERATURE: Event<Temperature> = Event::new("temperature");
+
+#[test]
+fn test_typed_on_and_emit_across_threads() {
+    let emitter = Arc::new(MultiThreadEventEmitter::new());
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let received_clone = received.clone();
+    emitter.on_typed(&TEMPERATURE, move |t| {
+        received_clone.lock().unwrap().push(t.0);
+    });
+
+    let e = emitter.clone();
+    thread::spawn(move || e.emit_typed(&TEMPERATURE, Temperature(21.5)))
+        .join()
+        .unwrap();
+
+    assert_eq!(*received.lock().unwrap(), vec![21.5]);
+}
+
+#[test]
+fn test_once_typed_skips_mismatched_payload() {
+    let emitter = MultiThreadEventEmitter::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let received_clone = received.clone();
+    emitter.once_typed(&TEMPERATURE, move |t| {
+        received_clone.lock().unwrap().push(t.0);
+    });
+
+    // 类型不匹配的 emit 不会消耗 once 监听器
+    emitter.emit("temperature", ts_args![21]);
+    emitter.emit_typed(&TEMPERATURE, Temperature(18.0));
+    emitter.emit_typed(&TEMPERATURE, Temperature(19.0));
+
+    assert_eq!(*received.lock().unwrap(), vec![18.0]);
+    assert_eq!(emitter.listener_count("temperature"), 0);
+}
+
+#[test]
+fn test_introspection() {
+    let emitter = MultiThreadEventEmitter::new();
+
+    let id = emitter.on_async_with(
+        "job",
+        ListenerOptions::new().label("worker").once(),
+        |_| Box::pin(async {}),
+    );
+    emitter.on("job", |_| {});
+    emitter.on_pattern("job.#", |_, _| {});
+
+    assert_eq!(emitter.listener_count("job"), 3);
+    assert_eq!(emitter.event_names(), vec!["job"]);
+    assert_eq!(emitter.event_patterns(), vec!["job.#"]);
+    assert_eq!(
+        emitter.raw_listeners("job")[0],
+        ListenerInfo {
+            id,
+            once: true,
+            kind: ListenerKind::Async,
+            priority: 0,
+            label: Some("worker".to_string()),
+        }
+    );
+
+    emitter.off_all("job");
+    assert!(emitter.event_names().is_empty());
+    assert_eq!(emitter.event_patterns(), vec!["job.#"]);
+}
+
+#[test]
+fn test_subscription_guard_is_send() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    let emitter = MultiThreadEventEmitter::new();
+    let counter = Arc::new(Mutex::new(0));
+
+    let counter_clone = counter.clone();
+    let sub = emitter.subscribe("tick", move |_| {
+        *counter_clone.lock().unwrap() += 1;
+    });
+    assert_send(&sub);
+
+    emitter.emit("tick", ts_args![]);
+    // 在其他线程中 drop 也能注销
+    thread::spawn(move || drop(sub)).join().unwrap();
+    emitter.emit("tick", ts_args![]);
+
+    assert_eq!(*counter.lock().unwrap(), 1);
+    assert_eq!(emitter.listener_count("tick"), 0);
+}
+
+#[test]
+fn test_max_listeners_warning() {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let warnings_clone = warnings.clone();
+    let emitter = MultiThreadEventEmitter::new()
+        .set_max_listeners(1)
+        .set_event_max_listeners("busy", 3)
+        .set_max_listeners_hook(move |e| warnings_clone.lock().unwrap().push(e.clone()));
+
+    for _ in 0..4 {
+        emitter.on("busy", |_| {});
+    }
+    emitter.once("tick", |_| {});
+    emitter.once("tick", |_| {});
+    let line = line!() - 1;
+
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!((warnings[0].event.as_str(), warnings[0].limit), ("busy", 3));
+    assert_eq!((warnings[1].event.as_str(), warnings[1].count), ("tick", 2));
+    assert_eq!(warnings[1].location.line(), line);
+}
+
+#[test]
+fn test_max_listeners_strict() {
+    let emitter = MultiThreadEventEmitter::new()
+        .set_max_listeners(1)
+        .set_strict_max_listeners(true);
+
+    emitter.on("tick", |_| {});
+    let err = emitter
+        .add_async_listener("tick", ListenerOptions::new(), |_| Box::pin(async {}))
+        .unwrap_err();
+    assert!(err.to_string().contains("'tick'"));
+    assert_eq!(emitter.listener_count("tick"), 1);
+}
+
+#[test]
+fn test_meta_events() {
+    let emitter = MultiThreadEventEmitter::new().set_meta_events(true);
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let log_clone = log.clone();
+    emitter.on(REMOVE_LISTENER_EVENT, move |args| {
+        let event = args[0].downcast_ref::<String>().unwrap().clone();
+        let id = *args[1].downcast_ref::<HandlerId>().unwrap();
+        log_clone.lock().unwrap().push(("remove", event, id));
+    });
+    let log_clone = log.clone();
+    emitter.on(NEW_LISTENER_EVENT, move |args| {
+        let event = args[0].downcast_ref::<String>().unwrap().clone();
+        let id = *args[1].downcast_ref::<HandlerId>().unwrap();
+        log_clone.lock().unwrap().push(("new", event, id));
+    });
+
+    let a = emitter.once("ready", |_| {});
+    let b = emitter.on_async("ready", |_| Box::pin(async {}));
+    assert!(emitter.off("ready", b));
+    emitter.emit("ready", ts_args![]);
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            ("new", NEW_LISTENER_EVENT.to_string(), 1),
+            ("new", "ready".to_string(), a),
+            ("new", "ready".to_string(), b),
+            ("remove", "ready".to_string(), b),
+            ("remove", "ready".to_string(), a),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_stream() {
+    use tokio_stream::StreamExt;
+
+    let emitter = MultiThreadEventEmitter::new();
+    let mut stream = emitter.stream("tick");
+
+    let emitter_clone = emitter.clone();
+    thread::spawn(move || {
+        for i in 0..3 {
+            emitter_clone.emit("tick", ts_args![i]);
+        }
+    });
+
+    let mut values = Vec::new();
+    while values.len() < 3 {
+        tokio::select! {
+            Some(args) = stream.next() => values.push(*args[0].downcast_ref::<i32>().unwrap()),
+            _ = tokio::time::sleep(Duration::from_secs(1)) => panic!("stream timed out"),
+        }
+    }
+    assert_eq!(values, vec![0, 1, 2]);
+
+    drop(stream);
+    assert_eq!(emitter.listener_count("tick"), 0);
+}
+
+#[tokio::test]
+async fn test_stream_ends_when_listener_removed() {
+    use tokio_stream::StreamExt;
+
+    let emitter = MultiThreadEventEmitter::new();
+    let mut stream = emitter.stream("tick");
+    emitter.emit("tick", ts_args![1]);
+
+    // 监听器被 off_all 移除后，缓冲区读完即结束
+    emitter.off_all("tick");
+    assert!(stream.next().await.is_some());
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_next() {
+    let emitter = MultiThreadEventEmitter::new();
+
+    let emitter_clone = emitter.clone();
+    let waiter = tokio::spawn(async move {
+        emitter_clone
+            .next_matching("num", |args| *args[0].downcast_ref::<i32>().unwrap() % 2 == 0)
+            .await
+    });
+    while emitter.listener_count("num") == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    emitter.emit("num", ts_args![1]);
+    emitter.emit("num", ts_args![2]);
+    let args = waiter.await.unwrap().unwrap();
+    assert_eq!(*args[0].downcast_ref::<i32>().unwrap(), 2);
+    assert_eq!(emitter.listener_count("num"), 0);
+
+    let result = emitter.next_timeout("never", Duration::from_millis(10)).await;
+    assert_eq!(result.unwrap_err(), NextError::Timeout);
+    assert_eq!(emitter.listener_count("never"), 0);
+}
+
+#[tokio::test]
+async fn test_retained_events() {
+    let emitter = MultiThreadEventEmitter::new().set_retention("ready", Retention::Last);
+    emitter.emit("ready", ts_args!["v1"]);
+
+    // 晚订阅的 next 立即拿到保留的事件
+    let args = emitter.next_timeout("ready", Duration::from_millis(100)).await.unwrap();
+    assert_eq!(*args[0].downcast_ref::<&str>().unwrap(), "v1");
+
+    let counter = Arc::new(Mutex::new(0));
+    let counter_clone = counter.clone();
+    emitter.on("ready", move |_| *counter_clone.lock().unwrap() += 1);
+    assert_eq!(*counter.lock().unwrap(), 1);
+
+    emitter.clear_retained("ready");
+    let counter_clone = counter.clone();
+    emitter.on("ready", move |_| *counter_clone.lock().unwrap() += 1);
+    assert_eq!(*counter.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_retained_replay_races_with_emit() {
+    let emitter = MultiThreadEventEmitter::new().set_retention("tick", Retention::LastN(1000));
+    let emitter_clone = emitter.clone();
+    let producer = thread::spawn(move || {
+        for i in 0..500 {
+            emitter_clone.emit("tick", ts_args![i]);
+        }
+    });
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen_clone = seen.clone();
+    emitter.on("tick", move |args| {
+        seen_clone.lock().unwrap().push(*args[0].downcast_ref::<i32>().unwrap());
+    });
+    producer.join().unwrap();
+
+    // 每个 payload 要么回放要么实时送达，恰好一次
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort();
+    assert_eq!(seen, (0..500).collect::<Vec<_>>());
+}
+
+struct Auth;
+
+impl ThreadSafeInterceptor for Auth {
+    fn before_emit(&self, _event: &mut String, args: &mut ThreadSafeArgs) -> bool {
+        // 拒绝未携带 token 的事件
+        args.first().and_then(|a| a.downcast_ref::<&str>()) == Some(&"token")
+    }
+
+    fn around_sync(&self, event: &str, _id: HandlerId, next: &mut dyn FnMut()) {
+        if event != "admin" {
+            next();
+        }
+    }
+}
+
+#[test]
+fn test_interceptors() {
+    let emitter = MultiThreadEventEmitter::new().with_interceptor(Auth);
+    let counter = Arc::new(Mutex::new(0));
+
+    for event in ["user", "admin"] {
+        let counter_clone = counter.clone();
+        emitter.on(event, move |_| *counter_clone.lock().unwrap() += 1);
+    }
+
+    emitter.emit("user", ts_args!["token"]);
+    emitter.emit("user", ts_args!["forged"]);
+    emitter.emit("admin", ts_args!["token"]);
+    assert_eq!(*counter.lock().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_filtered_listeners() {
+    let emitter = MultiThreadEventEmitter::new();
+    let counter = Arc::new(Mutex::new(0));
+    let is_urgent = |args: &ThreadSafeArgs| args[0].downcast_ref::<&str>() == Some(&"urgent");
+
+    let counter_clone = counter.clone();
+    emitter.on_filtered("alert", is_urgent, move |_| *counter_clone.lock().unwrap() += 1);
+    let counter_clone = counter.clone();
+    emitter.once_filtered_async("alert", is_urgent, move |_| {
+        let counter = counter_clone.clone();
+        Box::pin(async move { *counter.lock().unwrap() += 10 })
+    });
+
+    emitter.emit_and_wait("alert", ts_args!["info"]).await;
+    assert_eq!(*counter.lock().unwrap(), 0);
+    emitter.emit_and_wait("alert", ts_args!["urgent"]).await;
+    emitter.emit_and_wait("alert", ts_args!["urgent"]).await;
+    assert_eq!(*counter.lock().unwrap(), 12);
+}
+
+#[tokio::test]
+async fn test_request_response() {
+    let emitter = MultiThreadEventEmitter::new();
+    emitter.handle("add", |args| async move {
+        let a = *args[0].downcast_ref::<i32>().unwrap();
+        let b = *args[1].downcast_ref::<i32>().unwrap();
+        a + b
+    });
+
+    let sum: i32 = emitter
+        .request("add", ts_args![2, 3], Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(sum, 5);
+
+    // 普通 emit 不会触发 responder
+    emitter.emit_and_wait("add", ts_args![1, 1]).await;
+
+    let result = emitter.request::<i32>("missing", ts_args![], Duration::from_secs(1)).await;
+    assert_eq!(result.unwrap_err(), RequestError::NoResponder);
+
+    emitter.handle("slow", |_| async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    });
+    let result = emitter.request::<()>("slow", ts_args![], Duration::from_millis(20)).await;
+    assert_eq!(result.unwrap_err(), RequestError::Timeout);
+}
+
+#[tokio::test]
+async fn test_responders_are_not_listeners() {
+    let emitter = MultiThreadEventEmitter::new().set_retention("echo", Retention::Last);
+    let seen = Arc::new(Mutex::new(0));
+    let seen_clone = seen.clone();
+    emitter.on("echo", move |_args| *seen_clone.lock().unwrap() += 1);
+    let id = emitter.handle("echo", |args| async move { *args[0].downcast_ref::<i32>().unwrap() });
+    assert_eq!(emitter.listener_count("echo"), 1);
+
+    let reply: i32 = emitter.request("echo", ts_args![7], Duration::from_secs(1)).await.unwrap();
+    assert_eq!(reply, 7);
+    // 请求不会传给普通 listener，也不会被保留
+    assert_eq!(*seen.lock().unwrap(), 0);
+
+    // 后注册的 responder 不会收到之前的请求
+    let calls = Arc::new(Mutex::new(0));
+    let calls_clone = calls.clone();
+    emitter.handle("echo", move |_| {
+        let calls = calls_clone.clone();
+        async move { *calls.lock().unwrap() += 1 }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(*calls.lock().unwrap(), 0);
+
+    assert!(emitter.off("echo", id));
+    let result = emitter.request::<i32>("echo", ts_args![8], Duration::from_secs(1)).await;
+    assert_eq!(result.unwrap_err(), RequestError::NoResponder);
+    assert_eq!(*calls.lock().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_scatter_gather() {
+    let emitter = MultiThreadEventEmitter::new();
+    for (name, delay) in [("a", 0), ("b", 10), ("c", 5000)] {
+        emitter.handle("status", move |_| async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            name.to_string()
+        });
+    }
+
+    let mut replies: Vec<String> = emitter
+        .gather("status", ts_args![], Duration::from_millis(200))
+        .await
+        .unwrap();
+    replies.sort();
+    assert_eq!(replies, vec!["a", "b"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_sequential_dispatch() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build()
+        .set_event_dispatch_mode("write", DispatchMode::Sequential);
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    for (name, delay) in [("slow", 30), ("fast", 0)] {
+        let log_clone = log.clone();
+        emitter.on_async("write", move |args| {
+            let log = log_clone.clone();
+            let n = *args[0].downcast_ref::<i32>().unwrap();
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                log.lock().unwrap().push(format!("{name}{n}"));
+            })
+        });
+    }
+
+    emitter.emit("write", ts_args![1]);
+    emitter.emit_and_wait("write", ts_args![2]).await;
+    // 同一次 emit 按注册顺序，多次 emit 按 emit 顺序
+    assert_eq!(*log.lock().unwrap(), vec!["slow1", "fast1", "slow2", "fast2"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_sequential_dispatch_across_threads() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build()
+        .set_event_dispatch_mode("write", DispatchMode::Sequential);
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    for name in ["A", "B", "C"] {
+        let log_clone = log.clone();
+        emitter.on_async("write", move |args| {
+            let log = log_clone.clone();
+            let n = *args[0].downcast_ref::<i32>().unwrap();
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                log.lock().unwrap().push((n, name));
+            })
+        });
+    }
+
+    let barrier = Arc::new(std::sync::Barrier::new(16));
+    let handles: Vec<_> = (0..16)
+        .map(|n| {
+            let (emitter, barrier) = (emitter.clone(), barrier.clone());
+            thread::spawn(move || {
+                barrier.wait();
+                emitter.emit("write", ts_args![n]);
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    tokio::time::timeout(Duration::from_secs(3), async {
+        while log.lock().unwrap().len() < 48 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    // 并发 emit 的处理器不会交错：每次 emit 的 A、B、C 连续执行
+    let log = log.lock().unwrap();
+    for chunk in log.chunks(3) {
+        let n = chunk[0].0;
+        assert_eq!(chunk, [(n, "A"), (n, "B"), (n, "C")], "interleaved: {log:?}");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrency_limits() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build()
+        .set_max_concurrency(3)
+        .set_event_max_concurrency("query", 2);
+    // (当前运行数, 峰值)
+    let total = Arc::new((AtomicUsize::new(0), AtomicUsize::new(0)));
+    let query = Arc::new((AtomicUsize::new(0), AtomicUsize::new(0)));
+
+    let listeners = [
+        ("query", vec![total.clone(), query.clone()]),
+        ("cache", vec![total.clone()]),
+    ];
+    for (event, counters) in listeners {
+        emitter.on_async(event, move |_args| {
+            let counters = counters.clone();
+            Box::pin(async move {
+                for (running, peak) in counters.iter().map(|c| &**c) {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                for counter in &counters {
+                    counter.0.fetch_sub(1, Ordering::SeqCst);
+                }
+            })
+        });
+    }
+
+    // 超出上限的处理器排队等待，而不是同时运行
+    for _ in 0..5 {
+        emitter.emit("query", ts_args![]);
+        emitter.emit("cache", ts_args![]);
+    }
+    let summary = emitter.emit_and_wait("query", ts_args![]).await;
+    assert_eq!(summary.async_count, 1);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    // 事件上限 2 约束 query，发射器上限 3 约束全部事件
+    assert_eq!(query.1.load(Ordering::SeqCst), 2);
+    assert_eq!(total.1.load(Ordering::SeqCst), 3);
+    assert_eq!(total.0.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_listener_limit_does_not_starve_emitter_slots() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build()
+        .set_max_concurrency(2);
+    let fast_done = Arc::new(Mutex::new(0));
+
+    emitter.on_async_with("job", ListenerOptions::new().max_concurrency(1), |_args| {
+        Box::pin(tokio::time::sleep(Duration::from_millis(200)))
+    });
+    let fast_done_clone = fast_done.clone();
+    emitter.on_async("job", move |_args| {
+        let fast_done = fast_done_clone.clone();
+        Box::pin(async move {
+            *fast_done.lock().unwrap() += 1;
+        })
+    });
+
+    for _ in 0..3 {
+        emitter.emit("job", ts_args![]);
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // 排队等待自身名额的慢处理器不占用发射器名额，快处理器三次都立即完成
+    assert_eq!(*fast_done.lock().unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_zero_concurrency_limit_is_unlimited() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build()
+        .set_max_concurrency(0)
+        .set_event_max_concurrency("job", 0);
+    let done = Arc::new(Mutex::new(0));
+
+    let done_clone = done.clone();
+    emitter.on_async_with("job", ListenerOptions::new().max_concurrency(0), move |_args| {
+        let done = done_clone.clone();
+        Box::pin(async move {
+            *done.lock().unwrap() += 1;
+        })
+    });
+
+    // 上限为 0 表示不限制，而不是永远等待
+    emitter.emit_async("job", ts_args![]).await;
+    let summary = emitter.emit_and_wait("job", ts_args![]).await;
+    assert_eq!(summary.async_count, 1);
+    assert_eq!(*done.lock().unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_emit_async_backpressure() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
+    let done = Arc::new(AtomicUsize::new(0));
+
+    let done_clone = done.clone();
+    emitter.on_async_with("write", ListenerOptions::new().max_concurrency(1), move |_args| {
+        let done = done_clone.clone();
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            done.fetch_add(1, Ordering::SeqCst);
+        })
+    });
+
+    // 第二次 emit_async 要等第一个处理器释放名额后才返回
+    emitter.emit_async("write", ts_args![]).await;
+    assert_eq!(done.load(Ordering::SeqCst), 0);
+    emitter.emit_async("write", ts_args![]).await;
+    assert_eq!(done.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_off_aborts_in_flight_handlers() {
+    let finished = Arc::new(Mutex::new(Vec::new()));
+    let make = |emitter: &MultiThreadEventEmitter, name: &'static str| {
+        let finished_clone = finished.clone();
+        emitter.on_async("job", move |_args| {
+            let finished = finished_clone.clone();
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                finished.lock().unwrap().push(name);
+            })
+        })
+    };
+
+    let handle = tokio::runtime::Handle::current();
+    let detached = MultiThreadEventEmitter::builder().handle(handle.clone()).build();
+    let aborting = MultiThreadEventEmitter::builder()
+        .handle(handle)
+        .build()
+        .set_cancel_policy(CancelPolicy::Abort);
+    let detached_id = make(&detached, "detached");
+    let aborting_id = make(&aborting, "aborting");
+
+    detached.emit("job", ts_args![]);
+    aborting.emit("job", ts_args![]);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(detached.off("job", detached_id));
+    assert!(aborting.off("job", aborting_id));
+
+    // 默认策略让任务跑完，Abort 策略中止仍在运行的任务
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    assert_eq!(*finished.lock().unwrap(), vec!["detached"]);
+}
+
+#[tokio::test]
+async fn test_cancellable_listener_token() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let tx = Arc::new(Mutex::new(Some(tx)));
+
+    let id = emitter.on_async_cancellable("job", move |_args, token| {
+        let tx = tx.clone();
+        Box::pin(async move {
+            token.cancelled().await;
+            if let Some(tx) = tx.lock().unwrap().take() {
+                let _ = tx.send("cancelled");
+            }
+        })
+    });
+
+    emitter.emit("job", ts_args![]);
+    emitter.off("job", id);
+
+    let result = tokio::time::timeout(Duration::from_secs(1), rx).await;
+    assert_eq!(result.unwrap().unwrap(), "cancelled");
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_then_aborts() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
+    let finished = Arc::new(Mutex::new(Vec::new()));
+
+    for (name, delay) in [("fast", 10), ("slow", 1000)] {
+        let finished_clone = finished.clone();
+        emitter.on_async("job", move |_args| {
+            let finished = finished_clone.clone();
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                finished.lock().unwrap().push(name);
+            })
+        });
+    }
+    let finished_clone = finished.clone();
+    emitter.on_async_cancellable("job", move |_args, token| {
+        let finished = finished_clone.clone();
+        Box::pin(async move {
+            token.cancelled().await;
+            finished.lock().unwrap().push("cancellable");
+        })
+    });
+
+    emitter.emit("job", ts_args![]);
+    let (report, _) = tokio::join!(emitter.shutdown(Duration::from_millis(100)), async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        emitter.emit("job", ts_args![]);
+    });
+
+    // 快的处理器在期限内完成，慢的被中止，关闭期间的 emit 被拒绝
+    let mut finished = finished.lock().unwrap().clone();
+    finished.sort();
+    assert_eq!(finished, vec!["cancellable", "fast"]);
+    assert_eq!(report.cancelled.len(), 1);
+    assert_eq!(report.cancelled[0].event, "job");
+    assert_eq!(report.rejected, 1);
+
+    let summary = emitter.emit_and_wait("job", ts_args![]).await;
+    assert_eq!(summary, EmitSummary::default());
+}
+
+#[tokio::test]
+async fn test_shutdown_aborts_detached_handlers() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
+    let finished = Arc::new(Mutex::new(false));
+
+    let finished_clone = finished.clone();
+    let id = emitter.on_async("job", move |_args| {
+        let finished = finished_clone.clone();
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            *finished.lock().unwrap() = true;
+        })
+    });
+    emitter.emit("job", ts_args![]);
+    // Detach 下移除监听器，任务继续运行，但关闭时仍会被中止
+    assert!(emitter.off("job", id));
+
+    let report = emitter.shutdown(Duration::from_millis(20)).await;
+    assert_eq!(report.cancelled.len(), 1);
+    assert_eq!(report.cancelled[0].id, id);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!*finished.lock().unwrap());
+}
+
+#[test]
+fn test_once_fires_at_most_once_across_threads() {
+    use std::sync::Barrier;
+
+    for _ in 0..20 {
+        let emitter = MultiThreadEventEmitter::new();
+        let count = Arc::new(Mutex::new(0));
+
+        let count_clone = count.clone();
+        emitter.once("race", move |_args| {
+            *count_clone.lock().unwrap() += 1;
+        });
+
+        // 多个线程同时 emit，once 处理器只能被调用一次
+        let barrier = Arc::new(Barrier::new(8));
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let emitter = emitter.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    emitter.emit("race", ts_args![]);
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        assert_eq!(*count.lock().unwrap(), 1);
+        assert_eq!(emitter.listener_count("race"), 0);
+    }
+}
+
+#[test]
+fn test_once_registered_during_emit_survives() {
+    let emitter = MultiThreadEventEmitter::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let (emitter_clone, log_clone) = (emitter.clone(), log.clone());
+    emitter.once("tick", move |_args| {
+        log_clone.lock().unwrap().push("first");
+        let log = log_clone.clone();
+        emitter_clone.once("tick", move |_args| log.lock().unwrap().push("second"));
+    });
+
+    // 第一次 emit 中注册的 once 处理器没有运行，不能被移除
+    emitter.emit("tick", ts_args![]);
+    assert_eq!(emitter.listener_count("tick"), 1);
+    emitter.emit("tick", ts_args![]);
+    emitter.emit("tick", ts_args![]);
+
+    assert_eq!(*log.lock().unwrap(), vec!["first", "second"]);
+}
+
+#[tokio::test]
+async fn test_async_without_handle_uses_current_runtime() {
+    let emitter = MultiThreadEventEmitter::new();
+    let counter = Arc::new(Mutex::new(0));
+
+    let counter_clone = counter.clone();
+    emitter.on_async("job", move |_args| {
+        let counter = counter_clone.clone();
+        Box::pin(async move { *counter.lock().unwrap() += 1 })
+    });
+
+    let summary = emitter.emit_and_wait("job", ts_args![]).await;
+    assert_eq!(summary.async_count, 1);
+    assert_eq!(*counter.lock().unwrap(), 1);
+}
+
+// async-std 和 smol 的全局执行器总是可用
+#[cfg(not(any(feature = "async-std", feature = "smol")))]
+#[test]
+fn test_missing_runtime_reports_error() {
+    let emitter = MultiThreadEventEmitter::new();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+
+    let id = emitter.on_async("job", |_args| Box::pin(async {}));
+    let errors_clone = errors.clone();
+    emitter.on(ERROR_EVENT, move |args| {
+        let err = args[0]
+            .downcast_ref::<ListenerError<ThreadSafeHandlerError>>()
+            .unwrap();
+        assert!(err.error.downcast_ref::<NoRuntime>().is_some());
+        errors_clone.lock().unwrap().push(err.id);
+    });
+
+    // 没有运行时时不再静默跳过异步处理器
+    emitter.emit("job", ts_args![]);
+    assert_eq!(*errors.lock().unwrap(), vec![id]);
+}
+
+#[cfg(not(any(feature = "async-std", feature = "smol")))]
+#[test]
+fn test_missing_runtime_unhandled_error_does_not_panic() {
+    let emitter = MultiThreadEventEmitter::new();
+    let count = Arc::new(Mutex::new(0));
+
+    emitter.on_async("job", |_args| Box::pin(async {}));
+    let count_clone = count.clone();
+    emitter.on("job", move |_args| *count_clone.lock().unwrap() += 1);
+
+    // 没有 "error" 监听器时只打印一次警告，同步监听器照常执行
+    emitter.emit("job", ts_args![]);
+    emitter.emit("job", ts_args![]);
+    assert_eq!(*count.lock().unwrap(), 2);
+}
+
+#[cfg(not(any(feature = "async-std", feature = "smol")))]
+#[test]
+fn test_timeouts_without_runtime() {
+    use std::task::{Context, Poll, Waker};
+
+    fn poll_once<F: Future>(fut: F) -> Poll<F::Output> {
+        std::pin::pin!(fut).poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    let emitter = MultiThreadEventEmitter::new();
+    emitter.handle("add", |_args| async { 1 });
+
+    // 没有运行时就没有定时器，带超时的调用直接失败
+    let next = poll_once(emitter.next_timeout("ready", Duration::from_secs(1)));
+    assert_eq!(next.map(|result| result.err()), Poll::Ready(Some(NextError::NoRuntime)));
+    assert_eq!(emitter.listener_count("ready"), 0);
+
+    let request = poll_once(emitter.request::<i32>("add", ts_args![], Duration::from_secs(1)));
+    assert_eq!(request, Poll::Ready(Err(RequestError::NoRuntime)));
+    let gather = poll_once(emitter.gather::<i32>("add", ts_args![], Duration::from_secs(1)));
+    assert_eq!(gather, Poll::Ready(Err(RequestError::NoRuntime)));
+}
+
+#[cfg(not(any(feature = "async-std", feature = "smol")))]
+#[test]
+#[should_panic(expected = "no async runtime")]
+fn test_missing_runtime_panics() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .missing_runtime(MissingRuntime::Panic)
+        .build();
+
+    emitter.on_async("job", |_args| Box::pin(async {}));
+    emitter.emit("job", ts_args![]);
+}
+
+#[cfg(not(any(feature = "async-std", feature = "smol")))]
+#[test]
+fn test_missing_runtime_queue_until_attached() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .missing_runtime(MissingRuntime::Queue)
+        .build();
+    let counter = Arc::new(Mutex::new(0));
+
+    let counter_clone = counter.clone();
+    emitter.on_async("job", move |_args| {
+        let counter = counter_clone.clone();
+        Box::pin(async move { *counter.lock().unwrap() += 1 })
+    });
+    emitter.emit("job", ts_args![]);
+    emitter.emit("job", ts_args![]);
+    assert_eq!(*counter.lock().unwrap(), 0);
+
+    // 挂上运行时后排队的处理器开始执行
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    emitter.clone().attach_handle(rt.handle().clone());
+    rt.block_on(async { tokio::time::sleep(Duration::from_millis(20)).await });
+    assert_eq!(*counter.lock().unwrap(), 2);
+}
+
+#[cfg(not(any(feature = "async-std", feature = "smol")))]
+#[test]
+fn test_missing_runtime_internal() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .missing_runtime(MissingRuntime::Internal)
+        .build();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let tx = Arc::new(Mutex::new(tx));
+
+    emitter.on_async("job", move |args| {
+        let tx = tx.clone();
+        let n = *args[0].downcast_ref::<i32>().unwrap();
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            tx.lock().unwrap().send(n).unwrap();
+        })
+    });
+    emitter.emit("job", ts_args![7]);
+
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)), Ok(7));
+}
+
+#[test]
+fn test_custom_spawner() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let spawned = Arc::new(Mutex::new(0));
+
+    // 闭包就是一个自定义执行器
+    let (handle, spawned_clone) = (rt.handle().clone(), spawned.clone());
+    let emitter = MultiThreadEventEmitter::builder()
+        .spawner(move |fut| {
+            *spawned_clone.lock().unwrap() += 1;
+            handle.spawn(fut);
+        })
+        .build();
+    emitter.on_async("job", |_args| Box::pin(async {}));
+    let id = emitter.on_async("job", |_args| Box::pin(async { panic!("boom") }));
+
+    let summary = rt.block_on(emitter.emit_and_wait("job", ts_args![]));
+    assert_eq!(summary.async_count, 2);
+    assert_eq!(summary.panicked, vec![id]);
+    assert_eq!(*spawned.lock().unwrap(), 2);
+
+    // 执行器没有定时器时超时仍然生效
+    let result = rt.block_on(emitter.next_timeout("never", Duration::from_millis(10)));
+    assert_eq!(result.err(), Some(NextError::Timeout));
+}
+
+#[cfg(feature = "smol")]
+#[test]
+fn test_smol_backend() {
+    let emitter = MultiThreadEventEmitter::new();
+    let counter = Arc::new(Mutex::new(0));
+
+    let counter_clone = counter.clone();
+    emitter.on_async("job", move |_args| {
+        let counter = counter_clone.clone();
+        Box::pin(async move {
+            smol::Timer::after(Duration::from_millis(5)).await;
+            *counter.lock().unwrap() += 1;
+        })
+    });
+
+    // 不在 tokio 运行时中时落到 smol 的全局执行器上
+    let report = smol::block_on(async {
+        let summary = emitter.emit_and_wait("job", ts_args![]).await;
+        assert_eq!(summary.async_count, 1);
+        emitter.shutdown(Duration::from_millis(50)).await
+    });
+    assert_eq!(*counter.lock().unwrap(), 1);
+    assert!(report.cancelled.is_empty());
+}
+
+#[cfg(feature = "async-std")]
+#[test]
+fn test_async_std_backend() {
+    let emitter = MultiThreadEventEmitter::new();
+    emitter.on_async("slow", |_args| {
+        Box::pin(async_std::task::sleep(Duration::from_secs(10)))
+    });
+
+    let report = async_std::task::block_on(async {
+        emitter.emit("slow", ts_args![]);
+        emitter.shutdown(Duration::from_millis(20)).await
+    });
+    assert_eq!(report.cancelled.len(), 1);
+}
+
 #[tokio::test]
 async fn test_sync_on_and_emit() {
-    let emitter = MultiThreadEventEmitter::new().set_handle(tokio::runtime::Handle::current());
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
     let counter = Arc::new(Mutex::new(0));
     let counter_clone = counter.clone();
 
@@ -152,7 +1144,9 @@ async fn test_sync_on_and_emit() {
 
 #[tokio::test]
 async fn test_async_on_and_emit() {
-    let emitter = MultiThreadEventEmitter::new().set_handle(tokio::runtime::Handle::current());
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
     let counter = Arc::new(Mutex::new(0));
     let counter_clone = counter.clone();
 
@@ -171,9 +1165,229 @@ async fn test_async_on_and_emit() {
     assert_eq!(*counter.lock().unwrap(), 1);
 }
 
+#[tokio::test]
+async fn test_emit_and_wait() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
+    let counter = Arc::new(Mutex::new(0));
+
+    for delay in [5, 20] {
+        let counter_clone = counter.clone();
+        emitter.on_async("job", move |_args| {
+            let counter_clone = counter_clone.clone();
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                *counter_clone.lock().unwrap() += 1;
+            })
+        });
+    }
+    let id = emitter.on_async("job", |_args| Box::pin(async { panic!("boom") }));
+
+    let summary = emitter.emit_and_wait("job", ts_args![]).await;
+
+    // 无需 sleep，所有异步 handler 已完成
+    assert_eq!(*counter.lock().unwrap(), 2);
+    assert_eq!(summary.sync_count, 0);
+    assert_eq!(summary.async_count, 3);
+    assert_eq!(summary.panicked, vec![id]);
+}
+
+#[test]
+fn test_try_on_routes_error_to_error_event() {
+    let emitter = MultiThreadEventEmitter::new();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+
+    let id = emitter.try_on("parse", |args| {
+        let s = args[0].downcast_ref::<String>().unwrap();
+        s.parse::<i32>().map(|_| ())
+    });
+
+    let errors_clone = errors.clone();
+    emitter.on(ERROR_EVENT, move |args| {
+        let err = args[0]
+            .downcast_ref::<ListenerError<ThreadSafeHandlerError>>()
+            .unwrap();
+        errors_clone.lock().unwrap().push((err.id, err.event.clone()));
+    });
+
+    emitter.emit("parse", ts_args!["1".to_string()]);
+    emitter.emit("parse", ts_args!["x".to_string()]);
+
+    assert_eq!(*errors.lock().unwrap(), vec![(id, "parse".to_string())]);
+}
+
+#[test]
+fn test_wildcard_listener_handles_error() {
+    let emitter = MultiThreadEventEmitter::new();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+
+    let id = emitter.try_on("fail", |_args| Err("broken"));
+    let errors_clone = errors.clone();
+    emitter.on_pattern("*", move |event, args| {
+        if event == ERROR_EVENT {
+            let err = args[0].downcast_ref::<ListenerError<ThreadSafeHandlerError>>().unwrap();
+            errors_clone.lock().unwrap().push(err.id);
+        }
+    });
+
+    // 匹配 "error" 的通配监听器也算已处理，不会 panic
+    emitter.emit("fail", ts_args![]);
+    assert_eq!(*errors.lock().unwrap(), vec![id]);
+}
+
+#[test]
+#[should_panic(expected = "unhandled 'error' event")]
+fn test_try_on_unhandled_error_panics() {
+    let emitter = MultiThreadEventEmitter::new();
+
+    emitter.try_on("fail", |_args| Err("broken"));
+    emitter.emit("fail", ts_args![]);
+}
+
+#[tokio::test]
+async fn test_try_on_async_error() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
+    let errors = Arc::new(Mutex::new(0));
+
+    emitter.try_on_async("fail", |_args| Box::pin(async { Err("broken") }));
+
+    let errors_clone = errors.clone();
+    emitter.on(ERROR_EVENT, move |_args| {
+        *errors_clone.lock().unwrap() += 1;
+    });
+
+    let summary = emitter.emit_and_wait("fail", ts_args![]).await;
+
+    assert_eq!(*errors.lock().unwrap(), 1);
+    assert!(summary.panicked.is_empty());
+}
+
+#[test]
+fn test_config_set_on_clone_applies_to_original() {
+    let emitter = MultiThreadEventEmitter::new();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_clone = reports.clone();
+
+    // 配置在所有克隆间共享：在克隆上设置的 panic hook 对原实例同样生效
+    let _ = emitter.clone().set_panic_hook(move |p| {
+        reports_clone.lock().unwrap().push(p.message.clone());
+    });
+    emitter.on("work", |_args| panic!("boom"));
+
+    emitter.emit("work", ts_args![]);
+    assert_eq!(*reports.lock().unwrap(), vec!["boom".to_string()]);
+}
+
+#[tokio::test]
+async fn test_panic_isolation() {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_clone = reports.clone();
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build()
+        .set_panic_hook(move |p| {
+            reports_clone.lock().unwrap().push((p.id, p.message.clone()));
+        });
+    let counter = Arc::new(Mutex::new(0));
+
+    let sync_id = emitter.on("work", |_args| panic!("sync boom"));
+    let counter_clone = counter.clone();
+    emitter.on("work", move |_args| {
+        *counter_clone.lock().unwrap() += 1;
+    });
+    let async_id = emitter.on_async("work", |_args| Box::pin(async { panic!("async boom") }));
+
+    emitter.emit("work", ts_args![]);
+    let summary = emitter.emit_and_wait("work", ts_args![]).await;
+
+    assert_eq!(*counter.lock().unwrap(), 2);
+    assert_eq!(summary.panicked, vec![async_id]);
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.iter().filter(|(id, _)| *id == sync_id).count(), 2);
+    assert!(reports.contains(&(async_id, "async boom".to_string())));
+}
+
+#[tokio::test]
+async fn test_priority_order_for_sync_and_async() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
+    let sync_order = Arc::new(Mutex::new(Vec::new()));
+    let async_order = Arc::new(tokio::sync::Mutex::new(Vec::new()));
+
+    let order = sync_order.clone();
+    emitter.on("order", move |_| order.lock().unwrap().push("business"));
+    let order = sync_order.clone();
+    emitter.on_with("order", ListenerOptions::new().priority(1), move |_| {
+        order.lock().unwrap().push("audit")
+    });
+    let order = sync_order.clone();
+    emitter.prepend_listener("order", move |_| order.lock().unwrap().push("first"));
+
+    for (name, priority) in [("business", 0), ("audit", 1)] {
+        let order = async_order.clone();
+        emitter.on_async_with(
+            "order",
+            ListenerOptions::new().priority(priority),
+            move |_| {
+                let order = order.clone();
+                Box::pin(async move { order.lock().await.push(name) })
+            },
+        );
+    }
+
+    emitter.emit_and_wait("order", ts_args![]).await;
+
+    assert_eq!(*sync_order.lock().unwrap(), vec!["audit", "first", "business"]);
+    // current_thread runtime 按 spawn 顺序执行任务
+    assert_eq!(*async_order.lock().await, vec!["audit", "business"]);
+}
+
+#[tokio::test]
+async fn test_pattern_subscriptions_with_custom_delimiter() {
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build()
+        .set_delimiter('/');
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let seen_clone = seen.clone();
+    emitter.on_pattern("sensor/+/temperature", move |event, args| {
+        let value = *args[0].downcast_ref::<f64>().unwrap();
+        seen_clone.lock().unwrap().push((event.to_string(), value));
+    });
+
+    let seen_clone = seen.clone();
+    emitter.on_pattern_async("sensor/#", move |event, _args| {
+        let seen_clone = seen_clone.clone();
+        let event = event.to_string();
+        Box::pin(async move {
+            seen_clone.lock().unwrap().push((format!("async:{event}"), 0.0));
+        })
+    });
+
+    emitter.emit_and_wait("sensor/kitchen/temperature", ts_args![21.5]).await;
+    emitter.emit_and_wait("sensor/kitchen/humidity", ts_args![40.0]).await;
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            ("sensor/kitchen/temperature".to_string(), 21.5),
+            ("async:sensor/kitchen/temperature".to_string(), 0.0),
+            ("async:sensor/kitchen/humidity".to_string(), 0.0),
+        ]
+    );
+}
+
 #[tokio::test]
 async fn test_sync_and_async_mixed() {
-    let emitter = MultiThreadEventEmitter::new().set_handle(tokio::runtime::Handle::current());
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
     let sync_counter = Arc::new(Mutex::new(0));
     let async_counter = Arc::new(Mutex::new(0));
 
@@ -201,7 +1415,9 @@ async fn test_sync_and_async_mixed() {
 
 #[tokio::test]
 async fn test_once_sync() {
-    let emitter = MultiThreadEventEmitter::new().set_handle(tokio::runtime::Handle::current());
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
     let counter = Arc::new(Mutex::new(0));
     let counter_clone = counter.clone();
 
@@ -218,7 +1434,9 @@ async fn test_once_sync() {
 
 #[tokio::test]
 async fn test_once_async() {
-    let emitter = MultiThreadEventEmitter::new().set_handle(tokio::runtime::Handle::current());
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
     let counter = Arc::new(Mutex::new(0));
     let counter_clone = counter.clone();
 
@@ -241,7 +1459,9 @@ async fn test_once_async() {
 
 #[tokio::test]
 async fn test_off_and_off_all() {
-    let emitter = MultiThreadEventEmitter::new().set_handle(tokio::runtime::Handle::current());
+    let emitter = MultiThreadEventEmitter::builder()
+        .handle(tokio::runtime::Handle::current())
+        .build();
     let counter = Arc::new(Mutex::new(0));
     let counter_clone = counter.clone();
 
@@ -270,8 +1490,11 @@ async fn test_off_and_off_all() {
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn multithread_event_test() {
-    let emitter =
-        Arc::new(MultiThreadEventEmitter::new().set_handle(tokio::runtime::Handle::current()));
+    let emitter = Arc::new(
+        MultiThreadEventEmitter::builder()
+            .handle(tokio::runtime::Handle::current())
+            .build(),
+    );
 
     let counter = Arc::new(tokio::sync::Mutex::new(0usize));
 
@@ -341,3 +1564,75 @@ async fn multithread_event_test() {
         expected, result
     );
 }
+
+// 驻留的事件 ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct EventId(u32);
+
+impl EventKey for EventId {
+    type Ref = EventId;
+
+    fn name(event: &EventId) -> std::borrow::Cow<'_, str> {
+        format!("event-{}", event.0).into()
+    }
+}
+
+#[test]
+fn test_custom_event_key() {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let warnings_clone = warnings.clone();
+    let emitter = MultiThreadEventEmitter::<EventId>::default()
+        .set_event_max_listeners(&EventId(2), 1)
+        .set_max_listeners_hook(move |w| warnings_clone.lock().unwrap().push(w.event.clone()));
+    let total = Arc::new(Mutex::new(0));
+
+    let total_clone = total.clone();
+    let id = emitter.on(&EventId(1), move |args| {
+        *total_clone.lock().unwrap() += *args[0].downcast_ref::<i32>().unwrap();
+    });
+    emitter.on(&EventId(2), |_args| {});
+    emitter.on(&EventId(2), |_args| {});
+    assert_eq!(emitter.listener_count(&EventId(1)), 1);
+    // 警告中的事件名来自 EventKey::name
+    assert_eq!(*warnings.lock().unwrap(), vec!["event-2"]);
+
+    let emitter_clone = emitter.clone();
+    thread::spawn(move || {
+        emitter_clone.emit(&EventId(1), ts_args![2]);
+        emitter_clone.emit(&EventId(3), ts_args![100]);
+    })
+    .join()
+    .unwrap();
+    assert_eq!(*total.lock().unwrap(), 2);
+
+    // event_names 直接返回键
+    let mut names = emitter.event_names();
+    names.sort_by_key(|event| event.0);
+    assert_eq!(names, vec![EventId(1), EventId(2)]);
+
+    assert!(emitter.off(&EventId(1), id));
+    emitter.emit(&EventId(1), ts_args![5]);
+    assert_eq!(*total.lock().unwrap(), 2);
+}
+
+#[test]
+fn test_integer_event_key() {
+    let emitter = MultiThreadEventEmitter::<u32>::default();
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let received_clone = received.clone();
+    emitter.on(&7, move |args| {
+        received_clone.lock().unwrap().push(*args[0].downcast_ref::<i32>().unwrap());
+    });
+    // 整数键按十进制形式参与通配符匹配
+    let received_clone = received.clone();
+    emitter.on_pattern("7", move |event, _args| {
+        assert_eq!(event, "7");
+        received_clone.lock().unwrap().push(0);
+    });
+
+    emitter.emit(&7, ts_args![1]);
+    emitter.emit(&8, ts_args![2]);
+    assert_eq!(*received.lock().unwrap(), vec![1, 0]);
+    assert_eq!(emitter.event_names(), vec![7]);
+}
@@ -0,0 +1,72 @@
+// 只在关闭 std 时编译：cargo test --no-default-features --test no_std_test
+#![cfg(not(feature = "std"))]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use nodevent::args;
+use nodevent::types::*;
+use nodevent::*;
+
+#[test]
+fn test_on_and_emit_without_std() {
+    let emitter = SingleThreadEventEmitter::new();
+    let received = Rc::new(RefCell::new(Vec::<String>::new()));
+
+    let received_clone = received.clone();
+    emitter.on("data", move |args| {
+        if let Some(s) = args[0].downcast_ref::<&str>() {
+            received_clone.borrow_mut().push(s.to_string());
+        }
+    });
+
+    emitter.emit("data", args!["hello"]);
+    emitter.emit("data", args!["world"]);
+
+    assert_eq!(*received.borrow(), vec!["hello", "world"]);
+}
+
+#[test]
+fn test_once_and_off_without_std() {
+    let emitter = SingleThreadEventEmitter::new();
+    let count = Rc::new(RefCell::new(0));
+
+    let count_clone = count.clone();
+    emitter.once("tick", move |_| *count_clone.borrow_mut() += 1);
+    let count_clone = count.clone();
+    let id = emitter.on("tick", move |_| *count_clone.borrow_mut() += 10);
+
+    emitter.emit("tick", args![]);
+    // once 监听器触发后被移除
+    assert_eq!(emitter.listener_count("tick"), 1);
+
+    assert!(emitter.off("tick", id));
+    emitter.emit("tick", args![]);
+
+    assert_eq!(*count.borrow(), 11);
+    assert_eq!(emitter.listener_count("tick"), 0);
+}
+
+#[test]
+fn test_retention_without_std() {
+    let emitter = SingleThreadEventEmitter::new().set_retention("ready", Retention::Last);
+    let received = Rc::new(RefCell::new(Vec::<i32>::new()));
+
+    emitter.emit("ready", args![1]);
+    emitter.emit("ready", args![2]);
+
+    let received_clone = received.clone();
+    emitter.on("ready", move |args| {
+        if let Some(n) = args[0].downcast_ref::<i32>() {
+            received_clone.borrow_mut().push(*n);
+        }
+    });
+
+    assert_eq!(*received.borrow(), vec![2]);
+}
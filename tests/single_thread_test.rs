@@ -1,7 +1,9 @@
 use nodevent::*;
+use nodevent::types::*;
 use std::any::Any;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 use tokio::task::{self, LocalSet};
 
 #[test]
@@ -285,3 +287,1167 @@ async fn test_sync_and_async_mixed() {
         assert_eq!(*async_called.borrow(), 1);
     }).await;
 }
+
+#[derive(Debug, PartialEq)]
+struct OrderPlaced {
+    id: u32,
+}
+
+const ORDER_PLACED: Event<OrderPlaced> = Event::new("order_placed");
+
+#[test]
+fn test_typed_on_and_emit() {
+    let emitter = SingleThreadEventEmitter::new();
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    let received_clone = received.clone();
+    emitter.on_typed(&ORDER_PLACED, move |order| {
+        received_clone.borrow_mut().push(order.id);
+    });
+
+    let once_count = Rc::new(RefCell::new(0));
+    let once_clone = once_count.clone();
+    emitter.once_typed(&ORDER_PLACED, move |_| {
+        *once_clone.borrow_mut() += 1;
+    });
+
+    emitter.emit_typed(&ORDER_PLACED, OrderPlaced { id: 1 });
+    emitter.emit_typed(&ORDER_PLACED, OrderPlaced { id: 2 });
+    // 类型不匹配的动态 emit 会被忽略
+    emitter.emit("order_placed", args!["not an order"]);
+
+    assert_eq!(*received.borrow(), vec![1, 2]);
+    assert_eq!(*once_count.borrow(), 1);
+}
+
+#[test]
+fn test_once_typed_skips_mismatched_payload() {
+    let emitter = SingleThreadEventEmitter::new();
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    let received_clone = received.clone();
+    emitter.once_typed(&ORDER_PLACED, move |order| {
+        received_clone.borrow_mut().push(order.id);
+    });
+
+    // 类型不匹配的 emit 不会消耗 once 监听器
+    emitter.emit("order_placed", args!["not an order"]);
+    assert_eq!(emitter.listener_count("order_placed"), 1);
+
+    emitter.emit_typed(&ORDER_PLACED, OrderPlaced { id: 3 });
+    emitter.emit_typed(&ORDER_PLACED, OrderPlaced { id: 4 });
+
+    assert_eq!(*received.borrow(), vec![3]);
+    assert_eq!(emitter.listener_count("order_placed"), 0);
+}
+
+#[test]
+fn test_typed_payload_visible_to_dynamic_listener() {
+    let emitter = SingleThreadEventEmitter::new();
+    let received = Rc::new(RefCell::new(None));
+
+    let received_clone = received.clone();
+    emitter.on("order_placed", move |args| {
+        *received_clone.borrow_mut() = args[0].downcast_ref::<OrderPlaced>().map(|o| o.id);
+    });
+
+    emitter.emit_typed(&ORDER_PLACED, OrderPlaced { id: 7 });
+
+    assert_eq!(*received.borrow(), Some(7));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_emit_and_wait() {
+    let local = LocalSet::new();
+    local
+        .run_until(async {
+            let emitter = SingleThreadEventEmitter::new();
+            let done = Rc::new(RefCell::new(0));
+
+            emitter.on("job", |_args| {});
+
+            let done_clone = done.clone();
+            emitter.on_async("job", move |_args| {
+                let done_clone = done_clone.clone();
+                Box::pin(async move {
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    *done_clone.borrow_mut() += 1;
+                })
+            });
+
+            let id = emitter.on_async("job", |_args| Box::pin(async { panic!("boom") }));
+
+            let summary = emitter.emit_and_wait("job", args![]).await;
+
+            assert_eq!(*done.borrow(), 1);
+            assert_eq!(summary.sync_count, 1);
+            assert_eq!(summary.async_count, 2);
+            assert_eq!(summary.panicked, vec![id]);
+        })
+        .await;
+}
+
+#[test]
+fn test_try_on_routes_error_to_error_event() {
+    let emitter = SingleThreadEventEmitter::new();
+    let errors = Rc::new(RefCell::new(Vec::<String>::new()));
+    let after = Rc::new(RefCell::new(0));
+
+    let id = emitter.try_on("parse", |args| {
+        let s = args[0].downcast_ref::<&str>().unwrap();
+        s.parse::<i32>().map(|_| ())
+    });
+
+    let after_clone = after.clone();
+    emitter.on("parse", move |_args| {
+        *after_clone.borrow_mut() += 1;
+    });
+
+    let errors_clone = errors.clone();
+    emitter.on(ERROR_EVENT, move |args| {
+        let err = args[0].downcast_ref::<ListenerError<HandlerError>>().unwrap();
+        assert_eq!(err.id, id);
+        assert_eq!(err.event, "parse");
+        errors_clone.borrow_mut().push(err.error.to_string());
+    });
+
+    emitter.emit("parse", args!["42"]);
+    emitter.emit("parse", args!["nope"]);
+
+    assert_eq!(errors.borrow().len(), 1);
+    // 出错后其余 listener 仍然执行
+    assert_eq!(*after.borrow(), 2);
+}
+
+#[test]
+fn test_wildcard_listener_handles_error() {
+    let emitter = SingleThreadEventEmitter::new();
+    let errors = Rc::new(RefCell::new(Vec::new()));
+
+    let id = emitter.try_on("fail", |_args| Err("broken"));
+    let errors_clone = errors.clone();
+    emitter.on_pattern("*", move |event, args| {
+        if event == ERROR_EVENT {
+            let err = args[0].downcast_ref::<ListenerError<HandlerError>>().unwrap();
+            errors_clone.borrow_mut().push(err.id);
+        }
+    });
+
+    // 匹配 "error" 的通配监听器也算已处理，不会 panic
+    emitter.emit("fail", args![]);
+    assert_eq!(*errors.borrow(), vec![id]);
+}
+
+#[test]
+#[should_panic(expected = "unhandled 'error' event")]
+fn test_try_on_unhandled_error_panics() {
+    let emitter = SingleThreadEventEmitter::new();
+
+    emitter.try_on("fail", |_args| Err("broken"));
+    emitter.emit("fail", args![]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_try_on_async_error() {
+    let local = LocalSet::new();
+    local
+        .run_until(async {
+            let emitter = SingleThreadEventEmitter::new();
+            let errors = Rc::new(RefCell::new(0));
+
+            emitter.try_on_async("fail", |_args| Box::pin(async { Err("broken") }));
+
+            let errors_clone = errors.clone();
+            emitter.on(ERROR_EVENT, move |_args| {
+                *errors_clone.borrow_mut() += 1;
+            });
+
+            let summary = emitter.emit_and_wait("fail", args![]).await;
+
+            assert_eq!(*errors.borrow(), 1);
+            assert!(summary.panicked.is_empty());
+
+            emitter.off_all(ERROR_EVENT);
+            let summary = emitter.emit_and_wait("fail", args![]).await;
+            assert_eq!(summary.panicked.len(), 1);
+        })
+        .await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_panic_isolation() {
+    let local = LocalSet::new();
+    local
+        .run_until(async {
+            let reports = Rc::new(RefCell::new(Vec::<ListenerPanic>::new()));
+            let reports_clone = reports.clone();
+            let emitter = SingleThreadEventEmitter::new().set_panic_hook(move |p| {
+                reports_clone.borrow_mut().push(p.clone());
+            });
+            let called = Rc::new(RefCell::new(0));
+
+            let sync_id = emitter.on("work", |_args| panic!("sync boom"));
+            let called_clone = called.clone();
+            emitter.on("work", move |_args| {
+                *called_clone.borrow_mut() += 1;
+            });
+            let async_id =
+                emitter.on_async("work", |_args| Box::pin(async { panic!("async boom") }));
+
+            let summary = emitter.emit_and_wait("work", args![]).await;
+
+            assert_eq!(*called.borrow(), 1);
+            assert_eq!(summary.panicked, vec![async_id]);
+            assert_eq!(
+                *reports.borrow(),
+                vec![
+                    ListenerPanic {
+                        event: "work".to_string(),
+                        id: sync_id,
+                        message: "sync boom".to_string(),
+                    },
+                    ListenerPanic {
+                        event: "work".to_string(),
+                        id: async_id,
+                        message: "async boom".to_string(),
+                    },
+                ]
+            );
+        })
+        .await;
+}
+
+#[test]
+fn test_priority_and_prepend_order() {
+    let emitter = SingleThreadEventEmitter::new();
+    let order = Rc::new(RefCell::new(Vec::new()));
+
+    let push = |name: &'static str| {
+        let order = order.clone();
+        move |_args: Args| order.borrow_mut().push(name)
+    };
+
+    emitter.on("order", push("business"));
+    emitter.on_with("order", ListenerOptions::new().priority(10), push("audit"));
+    emitter.prepend_listener("order", push("prepended"));
+    emitter.on_with("order", ListenerOptions::new().priority(-1), push("cleanup"));
+    emitter.prepend_once_listener("order", push("once_first"));
+    emitter.on_with(
+        "order",
+        ListenerOptions::new().priority(10).prepend(),
+        push("validation"),
+    );
+
+    emitter.emit("order", args![]);
+    assert_eq!(
+        *order.borrow(),
+        vec![
+            "validation",
+            "audit",
+            "once_first",
+            "prepended",
+            "business",
+            "cleanup"
+        ]
+    );
+
+    order.borrow_mut().clear();
+    emitter.emit("order", args![]);
+    assert_eq!(
+        *order.borrow(),
+        vec!["validation", "audit", "prepended", "business", "cleanup"]
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_async_priority_order() {
+    let local = LocalSet::new();
+    local
+        .run_until(async {
+            let emitter = SingleThreadEventEmitter::new();
+            let order = Rc::new(RefCell::new(Vec::new()));
+
+            for (name, priority) in [("low", 0), ("high", 5)] {
+                let order = order.clone();
+                emitter.on_async_with(
+                    "order",
+                    ListenerOptions::new().priority(priority),
+                    move |_args| {
+                        order.borrow_mut().push(name);
+                        Box::pin(async {})
+                    },
+                );
+            }
+
+            emitter.emit_and_wait("order", args![]).await;
+            assert_eq!(*order.borrow(), vec!["high", "low"]);
+        })
+        .await;
+}
+
+#[test]
+fn test_pattern_subscriptions() {
+    let emitter = SingleThreadEventEmitter::new();
+    let seen = Rc::new(RefCell::new(Vec::<String>::new()));
+
+    let seen_clone = seen.clone();
+    emitter.on("order.created", move |_args| {
+        seen_clone.borrow_mut().push("exact".to_string());
+    });
+    let seen_clone = seen.clone();
+    let single = emitter.on_pattern("order.*", move |event, _args| {
+        seen_clone.borrow_mut().push(format!("*:{event}"));
+    });
+    let seen_clone = seen.clone();
+    emitter.on_pattern("order.#", move |event, _args| {
+        seen_clone.borrow_mut().push(format!("#:{event}"));
+    });
+    let seen_clone = seen.clone();
+    emitter.on_pattern_with(
+        "*.created",
+        ListenerOptions::new().once(),
+        move |event, _args| {
+            seen_clone.borrow_mut().push(format!("once:{event}"));
+        },
+    );
+
+    emitter.emit("order.created", args![]);
+    assert_eq!(
+        *seen.borrow(),
+        vec!["exact", "*:order.created", "#:order.created", "once:order.created"]
+    );
+
+    seen.borrow_mut().clear();
+    emitter.emit("order.created", args![]);
+    emitter.emit("order.item.added", args![]);
+    emitter.emit("user.created", args![]);
+    assert_eq!(
+        *seen.borrow(),
+        vec!["exact", "*:order.created", "#:order.created", "#:order.item.added"]
+    );
+
+    seen.borrow_mut().clear();
+    assert!(emitter.off("order.*", single));
+    emitter.off_all("order.#");
+    emitter.emit("order.shipped", args![]);
+    assert!(seen.borrow().is_empty());
+}
+
+#[test]
+fn test_once_pattern_removed_by_registered_pattern() {
+    let emitter = SingleThreadEventEmitter::new().set_meta_events(true);
+    let removed = Rc::new(RefCell::new(Vec::new()));
+    let calls = Rc::new(RefCell::new(0));
+
+    let removed_clone = removed.clone();
+    emitter.on(REMOVE_LISTENER_EVENT, move |args| {
+        let pattern = args[0].downcast_ref::<String>().unwrap().clone();
+        removed_clone.borrow_mut().push(pattern);
+    });
+    emitter.on_pattern("order.+", |_event, _args| {});
+    let calls_clone = calls.clone();
+    let id = emitter.on_pattern_with("order.**", ListenerOptions::new().once(), move |_, _| {
+        *calls_clone.borrow_mut() += 1;
+    });
+
+    emitter.emit("order.created", args![]);
+    emitter.emit("order.created", args![]);
+    assert_eq!(*calls.borrow(), 1);
+    // removeListener 携带注册时的模式，与 newListener 一致
+    assert_eq!(*removed.borrow(), vec!["order.**".to_string()]);
+    assert_eq!(emitter.event_patterns(), vec!["order.*".to_string()]);
+    assert!(!emitter.off("order.#", id));
+}
+
+#[test]
+fn test_pattern_respects_priority() {
+    let emitter = SingleThreadEventEmitter::new();
+    let order = Rc::new(RefCell::new(Vec::new()));
+
+    let order_clone = order.clone();
+    emitter.on("order.created", move |_args| order_clone.borrow_mut().push("exact"));
+    let order_clone = order.clone();
+    emitter.on_pattern_with(
+        "order.#",
+        ListenerOptions::new().priority(10),
+        move |_event, _args| order_clone.borrow_mut().push("audit"),
+    );
+
+    emitter.emit("order.created", args![]);
+    assert_eq!(*order.borrow(), vec!["audit", "exact"]);
+}
+
+#[test]
+fn test_pattern_prepend() {
+    let emitter = SingleThreadEventEmitter::new();
+    let order = Rc::new(RefCell::new(Vec::new()));
+
+    for (pattern, name) in [("order.*", "first"), ("order.#", "second")] {
+        let order_clone = order.clone();
+        emitter.on_pattern(pattern, move |_event, _args| order_clone.borrow_mut().push(name));
+    }
+    for (pattern, name) in [("order.*", "prepended"), ("order.#", "prepended_last")] {
+        let order_clone = order.clone();
+        emitter.on_pattern_with(pattern, ListenerOptions::new().prepend(), move |_event, _args| {
+            order_clone.borrow_mut().push(name)
+        });
+    }
+
+    // prepend 的模式监听器排在同优先级之前，后注册的更靠前
+    emitter.emit("order.created", args![]);
+    assert_eq!(*order.borrow(), vec!["prepended_last", "prepended", "first", "second"]);
+}
+
+#[test]
+fn test_introspection() {
+    let emitter = SingleThreadEventEmitter::new();
+
+    let a = emitter.on_with("order.created", ListenerOptions::new().label("audit"), |_| {});
+    let b = emitter.once("order.created", |_| {});
+    let c = emitter.on_async("order.created", |_| Box::pin(async {}));
+    let p = emitter.on_pattern("order.*", |_, _| {});
+    emitter.on("user.created", |_| {});
+
+    assert_eq!(emitter.listener_count("order.created"), 4);
+    assert_eq!(emitter.listener_count("order.shipped"), 1);
+    assert_eq!(emitter.listener_count("nothing"), 0);
+    let mut names = emitter.event_names();
+    names.sort();
+    assert_eq!(names, vec!["order.created", "user.created"]);
+    assert_eq!(emitter.event_patterns(), vec!["order.*"]);
+
+    let infos = emitter.listeners("order.created");
+    assert_eq!(
+        infos.iter().map(|i| i.id).collect::<Vec<_>>(),
+        vec![a, b, c, p]
+    );
+    assert_eq!(infos[0].label.as_deref(), Some("audit"));
+    assert!(infos[1].once);
+    assert_eq!(infos[2].kind, ListenerKind::Async);
+    assert_eq!(infos[3].kind, ListenerKind::Sync);
+
+    assert_eq!(emitter.raw_listeners("order.created").len(), 3);
+    assert_eq!(emitter.raw_listeners("order.*")[0].id, p);
+
+    assert!(emitter.off("order.created", c));
+    emitter.emit("order.created", args![]);
+    assert_eq!(emitter.listener_count("order.created"), 2);
+}
+
+#[test]
+fn test_subscription_guard() {
+    let emitter = SingleThreadEventEmitter::new();
+    let count = Rc::new(RefCell::new(0));
+
+    let count_clone = count.clone();
+    let sub = emitter.subscribe("tick", move |_args| {
+        *count_clone.borrow_mut() += 1;
+    });
+    emitter.emit("tick", args![]);
+    drop(sub);
+    emitter.emit("tick", args![]);
+    assert_eq!(*count.borrow(), 1);
+
+    let count_clone = count.clone();
+    let id = emitter
+        .subscribe("tick", move |_args| {
+            *count_clone.borrow_mut() += 1;
+        })
+        .forget();
+    emitter.emit("tick", args![]);
+    assert_eq!(*count.borrow(), 2);
+    assert!(emitter.subscription("tick", id).unsubscribe());
+    assert_eq!(emitter.listener_count("tick"), 0);
+}
+
+#[test]
+fn test_subscription_does_not_keep_emitter_alive() {
+    let emitter = SingleThreadEventEmitter::new();
+    let weak = emitter.downgrade();
+    let sub = emitter.subscribe("tick", |_args| {});
+
+    drop(emitter);
+    assert!(weak.upgrade().is_none());
+    assert!(!sub.unsubscribe());
+}
+
+#[test]
+fn test_weak_handle_in_hook_does_not_leak() {
+    let emitter = SingleThreadEventEmitter::new();
+    let handle = emitter.downgrade();
+    let marker = Rc::new(());
+    let alive = Rc::downgrade(&marker);
+
+    // hook 通过弱引用访问 emitter，不应形成循环引用
+    let emitter = emitter.set_panic_hook(move |_panic| {
+        let _ = (&marker, handle.upgrade());
+    });
+    let sub = emitter.subscribe("tick", |_args| {});
+
+    drop(emitter);
+    assert!(alive.upgrade().is_none());
+    assert!(!sub.unsubscribe());
+}
+
+#[test]
+fn test_max_listeners_warning() {
+    let warnings = Rc::new(RefCell::new(Vec::new()));
+    let warnings_clone = warnings.clone();
+    let emitter = SingleThreadEventEmitter::new()
+        .set_max_listeners(2)
+        .set_event_max_listeners("unlimited", 0)
+        .set_max_listeners_hook(move |e| warnings_clone.borrow_mut().push(e.clone()));
+
+    for _ in 0..5 {
+        emitter.on("tick", |_args| {});
+        emitter.on("unlimited", |_args| {});
+    }
+    let line = line!() - 3;
+
+    // 每次越过上限只警告一次
+    let warnings = warnings.borrow();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].event, "tick");
+    assert_eq!(warnings[0].count, 3);
+    assert_eq!(warnings[0].limit, 2);
+    assert_eq!(warnings[0].location.file(), file!());
+    assert_eq!(warnings[0].location.line(), line);
+    assert_eq!(emitter.listener_count("tick"), 5);
+}
+
+#[test]
+fn test_max_listeners_strict() {
+    let emitter = SingleThreadEventEmitter::new()
+        .set_event_max_listeners("tick", 1)
+        .set_strict_max_listeners(true);
+
+    emitter.once("tick", |_args| {});
+    let err = emitter
+        .add_listener("tick", ListenerOptions::new(), |_args| {})
+        .unwrap_err();
+    assert_eq!(err.count, 2);
+    assert_eq!(emitter.listener_count("tick"), 1);
+
+    // 未设置上限的事件不受影响
+    assert!(emitter.add_listener("other", ListenerOptions::new(), |_args| {}).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "possible listener leak")]
+fn test_max_listeners_strict_panics_on_on() {
+    let emitter = SingleThreadEventEmitter::new()
+        .set_max_listeners(1)
+        .set_strict_max_listeners(true);
+
+    emitter.on("tick", |_args| {});
+    emitter.on("tick", |_args| {});
+}
+
+#[test]
+fn test_meta_events() {
+    let emitter = SingleThreadEventEmitter::new().set_meta_events(true);
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    for meta in [NEW_LISTENER_EVENT, REMOVE_LISTENER_EVENT] {
+        let log_clone = log.clone();
+        emitter.on(meta, move |args| {
+            let event = args[0].downcast_ref::<String>().unwrap().clone();
+            let id = *args[1].downcast_ref::<HandlerId>().unwrap();
+            log_clone.borrow_mut().push((meta, event, id));
+        });
+    }
+    log.borrow_mut().clear();
+
+    let a = emitter.on("tick", |_args| {});
+    let b = emitter.once("tick", |_args| {});
+    let c = emitter.on_async("tick", |_args| Box::pin(async {}));
+    let p = emitter.on_pattern_with("tick.#", ListenerOptions::new().once(), |_event, _args| {});
+    assert!(emitter.off("tick", c));
+    // once 监听器触发后被移除
+    emitter.emit("tick", args![]);
+    emitter.off_all("tick");
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            (NEW_LISTENER_EVENT, "tick".to_string(), a),
+            (NEW_LISTENER_EVENT, "tick".to_string(), b),
+            (NEW_LISTENER_EVENT, "tick".to_string(), c),
+            (NEW_LISTENER_EVENT, "tick.#".to_string(), p),
+            (REMOVE_LISTENER_EVENT, "tick".to_string(), c),
+            (REMOVE_LISTENER_EVENT, "tick".to_string(), b),
+            (REMOVE_LISTENER_EVENT, "tick.#".to_string(), p),
+            (REMOVE_LISTENER_EVENT, "tick".to_string(), a),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_stream() {
+    use tokio_stream::StreamExt;
+
+    let emitter = SingleThreadEventEmitter::new();
+    let mut stream = emitter.stream("tick");
+    assert_eq!(emitter.listener_count("tick"), 1);
+
+    emitter.emit("tick", args![1]);
+    emitter.emit("tick", args![2]);
+    let values: Vec<i32> = (&mut stream)
+        .take(2)
+        .map(|args| *args[0].downcast_ref::<i32>().unwrap())
+        .collect()
+        .await;
+    assert_eq!(values, vec![1, 2]);
+
+    // 流被 drop 后自动注销监听器
+    drop(stream);
+    assert_eq!(emitter.listener_count("tick"), 0);
+}
+
+#[tokio::test]
+async fn test_stream_ends_when_listener_removed() {
+    use tokio_stream::StreamExt;
+
+    let emitter = SingleThreadEventEmitter::new();
+    let mut stream = emitter.stream("tick");
+    emitter.emit("tick", args![1]);
+
+    // 监听器被 off_all 移除后，缓冲区读完即结束
+    emitter.off_all("tick");
+    assert!(stream.next().await.is_some());
+    assert!(stream.next().await.is_none());
+
+    // emitter 被 drop 后流同样结束
+    let mut stream = emitter.stream("tick");
+    drop(emitter);
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_stream_overflow() {
+    use tokio_stream::StreamExt;
+
+    let emitter = SingleThreadEventEmitter::new();
+    let mut oldest = emitter.stream_with("tick", StreamOptions::new().capacity(2));
+    let mut newest = emitter.stream_with(
+        "tick",
+        StreamOptions::new()
+            .capacity(2)
+            .overflow(OverflowPolicy::DropNewest),
+    );
+
+    for i in 0..5 {
+        emitter.emit("tick", args![i]);
+    }
+    assert_eq!(oldest.dropped(), 3);
+    assert_eq!(newest.dropped(), 3);
+
+    let first = |args: Args| *args[0].downcast_ref::<i32>().unwrap();
+    assert_eq!(first(oldest.next().await.unwrap()), 3);
+    assert_eq!(first(oldest.next().await.unwrap()), 4);
+    assert_eq!(first(newest.next().await.unwrap()), 0);
+    assert_eq!(first(newest.next().await.unwrap()), 1);
+}
+
+#[tokio::test]
+async fn test_next() {
+    let emitter = SingleThreadEventEmitter::new();
+
+    let ready = emitter.next("ready");
+    let big = emitter.next_matching("num", |args| *args[0].downcast_ref::<i32>().unwrap() > 10);
+    assert_eq!(emitter.listener_count("ready"), 1);
+
+    emitter.emit("ready", args!["ok"]);
+    emitter.emit("num", args![1]);
+    emitter.emit("num", args![42]);
+
+    let args = ready.await.unwrap();
+    assert_eq!(*args[0].downcast_ref::<&str>().unwrap(), "ok");
+    let args = big.await.unwrap();
+    assert_eq!(*args[0].downcast_ref::<i32>().unwrap(), 42);
+    assert_eq!(emitter.listener_count("ready"), 0);
+    assert_eq!(emitter.listener_count("num"), 0);
+}
+
+#[tokio::test]
+async fn test_next_cancel_and_timeout() {
+    let emitter = SingleThreadEventEmitter::new();
+
+    // 未等待就丢弃 future，隐藏的监听器也会被移除
+    drop(emitter.next("ready"));
+    assert_eq!(emitter.listener_count("ready"), 0);
+
+    let result = emitter.next_timeout("ready", Duration::from_millis(10)).await;
+    assert_eq!(result.unwrap_err(), NextError::Timeout);
+    assert_eq!(emitter.listener_count("ready"), 0);
+
+    let pending = emitter.next("ready");
+    emitter.off_all("ready");
+    assert_eq!(pending.await.unwrap_err(), NextError::Removed);
+}
+
+#[test]
+fn test_retained_events() {
+    let emitter = SingleThreadEventEmitter::new()
+        .set_retention("config_loaded", Retention::Last)
+        .set_retention("log", Retention::LastN(2));
+
+    emitter.emit("config_loaded", args![1]);
+    emitter.emit("config_loaded", args![2]);
+    for i in 0..3 {
+        emitter.emit("log", args![i]);
+    }
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let seen_clone = seen.clone();
+    emitter.on("config_loaded", move |args| {
+        seen_clone.borrow_mut().push(*args[0].downcast_ref::<i32>().unwrap());
+    });
+    let seen_clone = seen.clone();
+    emitter.on("log", move |args| {
+        seen_clone.borrow_mut().push(*args[0].downcast_ref::<i32>().unwrap() * 10);
+    });
+    // once 监听器只接收一次回放，随后被移除
+    let seen_clone = seen.clone();
+    emitter.once("log", move |args| {
+        seen_clone.borrow_mut().push(*args[0].downcast_ref::<i32>().unwrap() * 100);
+    });
+    assert_eq!(*seen.borrow(), vec![2, 10, 20, 100]);
+    assert_eq!(emitter.listener_count("log"), 1);
+
+    emitter.emit("log", args![3]);
+    assert_eq!(*seen.borrow(), vec![2, 10, 20, 100, 30]);
+
+    emitter.clear_retained("config_loaded");
+    let count = Rc::new(RefCell::new(0));
+    let count_clone = count.clone();
+    emitter.on("config_loaded", move |_args| *count_clone.borrow_mut() += 1);
+    assert_eq!(*count.borrow(), 0);
+}
+
+#[test]
+fn test_retained_emit_from_new_listener_hook() {
+    let emitter = SingleThreadEventEmitter::new()
+        .set_retention("config_loaded", Retention::Last)
+        .set_meta_events(true);
+    emitter.emit("config_loaded", args![1]);
+
+    let emitter_clone = emitter.clone();
+    emitter.on(NEW_LISTENER_EVENT, move |args| {
+        if args[0].downcast_ref::<String>().unwrap() == "config_loaded" {
+            emitter_clone.emit("config_loaded", args![2]);
+        }
+    });
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let seen_clone = seen.clone();
+    emitter.on("config_loaded", move |args| {
+        seen_clone.borrow_mut().push(*args[0].downcast_ref::<i32>().unwrap());
+    });
+    // 回放先于 newListener，newListener 中的 emit 只送达一次
+    assert_eq!(*seen.borrow(), vec![1, 2]);
+}
+
+#[test]
+fn test_retained_window() {
+    let window = Retention::Window(Duration::from_millis(20));
+    let emitter = SingleThreadEventEmitter::new().set_retention("tick", window);
+
+    emitter.emit("tick", args![]);
+    std::thread::sleep(Duration::from_millis(40));
+    emitter.emit("tick", args![]);
+
+    let count = Rc::new(RefCell::new(0));
+    let count_clone = count.clone();
+    emitter.on("tick", move |_args| *count_clone.borrow_mut() += 1);
+    assert_eq!(*count.borrow(), 1);
+}
+
+struct Tracer {
+    name: &'static str,
+    log: Rc<RefCell<Vec<String>>>,
+}
+
+impl Interceptor for Tracer {
+    fn before_emit(&self, event: &mut String, _args: &mut Args) -> bool {
+        if event == "legacy" {
+            *event = "modern".to_string();
+        }
+        event != "blocked"
+    }
+
+    fn around_sync(&self, event: &str, _id: HandlerId, next: &mut dyn FnMut()) {
+        self.log.borrow_mut().push(format!("{} before {event}", self.name));
+        next();
+        self.log.borrow_mut().push(format!("{} after {event}", self.name));
+    }
+
+    fn around_async(&self, _event: &str, _id: HandlerId, next: HandlerFuture) -> HandlerFuture {
+        let log = self.log.clone();
+        let name = self.name;
+        Box::pin(async move {
+            log.borrow_mut().push(format!("{name} async start"));
+            next.await;
+            log.borrow_mut().push(format!("{name} async end"));
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_interceptors() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let emitter = SingleThreadEventEmitter::new()
+        .with_interceptor(Tracer { name: "a", log: log.clone() })
+        .with_interceptor(Tracer { name: "b", log: log.clone() });
+
+    let log_clone = log.clone();
+    emitter.on("modern", move |_args| log_clone.borrow_mut().push("handler".to_string()));
+    emitter.on("blocked", |_args| panic!("dropped emits must not be dispatched"));
+
+    emitter.emit("legacy", args![]);
+    emitter.emit("blocked", args![]);
+    assert_eq!(
+        *log.borrow(),
+        vec!["a before modern", "b before modern", "handler", "b after modern", "a after modern"]
+    );
+
+    log.borrow_mut().clear();
+    let log_clone = log.clone();
+    emitter.on_async("job", move |_args| {
+        let log = log_clone.clone();
+        Box::pin(async move { log.borrow_mut().push("job".to_string()) })
+    });
+    LocalSet::new()
+        .run_until(emitter.emit_and_wait("job", args![]))
+        .await;
+    assert_eq!(
+        *log.borrow(),
+        vec!["a async start", "b async start", "job", "b async end", "a async end"]
+    );
+}
+
+#[test]
+fn test_filtered_listeners() {
+    let emitter = SingleThreadEventEmitter::new();
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let is_even = |args: &Args| *args[0].downcast_ref::<i32>().unwrap() % 2 == 0;
+
+    let seen_clone = seen.clone();
+    emitter.on_filtered("num", is_even, move |args| {
+        seen_clone.borrow_mut().push(*args[0].downcast_ref::<i32>().unwrap());
+    });
+    // 过滤器不匹配时 once 监听器不会被消耗
+    let seen_clone = seen.clone();
+    emitter.once_filtered(
+        "num",
+        |args| *args[0].downcast_ref::<i32>().unwrap() > 2,
+        move |args| seen_clone.borrow_mut().push(*args[0].downcast_ref::<i32>().unwrap() * 100),
+    );
+    let plain_once = emitter.once("num", |_args| {});
+
+    emitter.emit("num", args![1]);
+    assert_eq!(emitter.listener_count("num"), 2);
+    assert!(!emitter.off("num", plain_once));
+
+    for i in 2..6 {
+        emitter.emit("num", args![i]);
+    }
+    assert_eq!(*seen.borrow(), vec![2, 300, 4]);
+    assert_eq!(emitter.listener_count("num"), 1);
+}
+
+#[tokio::test]
+async fn test_filtered_async_listener() {
+    let emitter = SingleThreadEventEmitter::new();
+    let count = Rc::new(RefCell::new(0));
+
+    let count_clone = count.clone();
+    emitter.once_filtered_async(
+        "job",
+        |args| args[0].downcast_ref::<&str>() == Some(&"mine"),
+        move |_args| {
+            let count = count_clone.clone();
+            Box::pin(async move { *count.borrow_mut() += 1 })
+        },
+    );
+
+    LocalSet::new()
+        .run_until(async {
+            let summary = emitter.emit_and_wait("job", args!["theirs"]).await;
+            assert_eq!(summary.async_count, 0);
+            emitter.emit_and_wait("job", args!["mine"]).await;
+            emitter.emit_and_wait("job", args!["mine"]).await;
+        })
+        .await;
+    assert_eq!(*count.borrow(), 1);
+}
+
+#[tokio::test]
+async fn test_sequential_dispatch() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let make = |emitter: &SingleThreadEventEmitter| {
+        for (name, delay) in [("slow", 20), ("fast", 0)] {
+            let log_clone = log.clone();
+            emitter.on_async("write", move |_args| {
+                let log = log_clone.clone();
+                Box::pin(async move {
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    log.borrow_mut().push(name);
+                })
+            });
+        }
+    };
+
+    let concurrent = SingleThreadEventEmitter::new();
+    let sequential = SingleThreadEventEmitter::new().set_dispatch_mode(DispatchMode::Sequential);
+    make(&concurrent);
+    make(&sequential);
+
+    LocalSet::new()
+        .run_until(async {
+            concurrent.emit_and_wait("write", args![]).await;
+            assert_eq!(*log.borrow(), vec!["fast", "slow"]);
+
+            log.borrow_mut().clear();
+            sequential.emit("write", args![]);
+            sequential.emit_and_wait("write", args![]).await;
+            assert_eq!(*log.borrow(), vec!["slow", "fast", "slow", "fast"]);
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_concurrency_limits() {
+    let emitter = SingleThreadEventEmitter::new().set_max_concurrency(2);
+    let running = Rc::new(RefCell::new(0));
+    let peak = Rc::new(RefCell::new(0));
+
+    for options in [ListenerOptions::new(), ListenerOptions::new().max_concurrency(1)] {
+        let (running_clone, peak_clone) = (running.clone(), peak.clone());
+        emitter.on_async_with("query", options, move |_args| {
+            let (running, peak) = (running_clone.clone(), peak_clone.clone());
+            Box::pin(async move {
+                *running.borrow_mut() += 1;
+                let now = *running.borrow();
+                peak.replace_with(|peak| (*peak).max(now));
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                *running.borrow_mut() -= 1;
+            })
+        });
+    }
+
+    LocalSet::new()
+        .run_until(async {
+            emitter.emit("query", args![]);
+            emitter.emit("query", args![]);
+            let summary = emitter.emit_and_wait("query", args![]).await;
+            assert_eq!(summary.async_count, 2);
+
+            // emit_async 返回时两个处理器都已拿到名额开始运行
+            emitter.emit_async("query", args![]).await;
+            assert_eq!(*running.borrow(), 2);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        })
+        .await;
+    assert_eq!(*peak.borrow(), 2);
+    assert_eq!(*running.borrow(), 0);
+}
+
+#[tokio::test]
+async fn test_off_all_cancels_in_flight_handlers() {
+    let emitter = SingleThreadEventEmitter::new().set_cancel_policy(CancelPolicy::Abort);
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let log_clone = log.clone();
+    emitter.on_async("job", move |_args| {
+        let log = log_clone.clone();
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            log.borrow_mut().push("aborted task finished");
+        })
+    });
+    let log_clone = log.clone();
+    emitter.on_async_cancellable("job", move |_args, token| {
+        let log = log_clone.clone();
+        Box::pin(async move {
+            token.cancelled().await;
+            log.borrow_mut().push("token cancelled");
+        })
+    });
+
+    LocalSet::new()
+        .run_until(async {
+            emitter.emit("job", args![]);
+            tokio::task::yield_now().await;
+            emitter.off_all("job");
+            tokio::time::sleep(Duration::from_millis(80)).await;
+        })
+        .await;
+    // 被中止的任务不会完成，可取消的处理器看到了 token 被取消
+    assert_eq!(*log.borrow(), vec!["token cancelled"]);
+}
+
+#[tokio::test]
+async fn test_shutdown() {
+    let emitter = SingleThreadEventEmitter::new();
+    let count = Rc::new(RefCell::new(0));
+
+    let count_clone = count.clone();
+    emitter.on("tick", move |_args| *count_clone.borrow_mut() += 1);
+    let slow = emitter.on_async("tick", |_args| {
+        Box::pin(async { tokio::time::sleep(Duration::from_secs(1)).await })
+    });
+
+    let report = LocalSet::new()
+        .run_until(async {
+            emitter.emit("tick", args![]);
+            let report = emitter.shutdown(Duration::from_millis(20)).await;
+            // 关闭后 emit 不再分发
+            emitter.emit("tick", args![]);
+            report
+        })
+        .await;
+
+    assert_eq!(*count.borrow(), 1);
+    assert_eq!(report.cancelled, vec![CancelledTask { event: "tick".to_string(), id: slow }]);
+    assert_eq!(report.rejected, 0);
+}
+
+#[test]
+fn test_once_under_reentrant_emit() {
+    let emitter = SingleThreadEventEmitter::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    // 处理器内部再次 emit 同一事件时 once 处理器不会被重复调用
+    let (emitter_clone, log_clone) = (emitter.clone(), log.clone());
+    emitter.once("tick", move |_args| {
+        log_clone.borrow_mut().push("once");
+        emitter_clone.emit("tick", args![]);
+    });
+
+    // 在 emit 过程中注册的 once 处理器保留到下一次 emit
+    let (emitter_clone, log_clone) = (emitter.clone(), log.clone());
+    let registered = Rc::new(RefCell::new(false));
+    emitter.on_pattern("tick.#", move |_event, _args| {
+        if !registered.replace(true) {
+            let log = log_clone.clone();
+            emitter_clone.once("tick", move |_args| log.borrow_mut().push("late"));
+        }
+    });
+
+    emitter.emit("tick", args![]);
+    assert_eq!(*log.borrow(), vec!["once"]);
+    assert_eq!(emitter.raw_listeners("tick").len(), 1);
+
+    emitter.emit("tick", args![]);
+    assert_eq!(*log.borrow(), vec!["once", "late"]);
+}
+
+#[tokio::test]
+async fn test_local_spawner() {
+    let spawned = Rc::new(RefCell::new(0));
+
+    // 闭包就是一个自定义的本地执行器
+    let spawned_clone = spawned.clone();
+    let emitter = SingleThreadEventEmitter::new().set_local_spawner(move |fut| {
+        *spawned_clone.borrow_mut() += 1;
+        task::spawn_local(fut);
+    });
+    emitter.on_async("job", |_args| Box::pin(async {}));
+
+    let local = LocalSet::new();
+    let summary = local.run_until(emitter.emit_and_wait("job", args![])).await;
+    assert_eq!(summary.async_count, 1);
+    assert_eq!(*spawned.borrow(), 1);
+}
+
+#[cfg(feature = "smol")]
+#[test]
+fn test_smol_local_spawner() {
+    let executor = Rc::new(smol::LocalExecutor::new());
+    let emitter = SingleThreadEventEmitter::new()
+        .set_local_spawner(nodevent::runtime::SmolLocalSpawner(executor.clone()));
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let log_clone = log.clone();
+    emitter.on_async("job", move |args| {
+        let log = log_clone.clone();
+        let n = *args[0].downcast_ref::<i32>().unwrap();
+        Box::pin(async move {
+            smol::Timer::after(Duration::from_millis(5)).await;
+            log.borrow_mut().push(n);
+        })
+    });
+
+    let summary = smol::block_on(executor.run(emitter.emit_and_wait("job", args![1])));
+    assert_eq!(summary.async_count, 1);
+    assert_eq!(*log.borrow(), vec![1]);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum AppEvent {
+    Click,
+    Failed,
+    Added,
+}
+
+impl EventKey for AppEvent {
+    type Ref = AppEvent;
+
+    fn error_event() -> Option<&'static AppEvent> {
+        Some(&AppEvent::Failed)
+    }
+
+    fn new_listener_event() -> Option<&'static AppEvent> {
+        Some(&AppEvent::Added)
+    }
+}
+
+#[test]
+fn test_custom_event_key() {
+    let emitter = SingleThreadEventEmitter::<AppEvent>::default().set_meta_events(true);
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let names = Rc::new(RefCell::new(Vec::new()));
+
+    // 通配符按 EventKey::name 匹配，默认为 Debug 输出
+    let names_clone = names.clone();
+    emitter.on_pattern("*", move |event, _args| {
+        names_clone.borrow_mut().push(event.to_string());
+    });
+
+    // 元事件的 args[0] 是键本身
+    let log_clone = log.clone();
+    emitter.on(&AppEvent::Added, move |args| {
+        let event = args[0].downcast_ref::<AppEvent>().unwrap();
+        log_clone.borrow_mut().push(format!("added {event:?}"));
+    });
+
+    let log_clone = log.clone();
+    emitter.on(&AppEvent::Click, move |args| {
+        let n = args[0].downcast_ref::<i32>().unwrap();
+        log_clone.borrow_mut().push(format!("click {n}"));
+    });
+
+    // 处理器错误路由到 error_event
+    let log_clone = log.clone();
+    emitter.on(&AppEvent::Failed, move |args| {
+        let error = args[0].downcast_ref::<ListenerError<HandlerError>>().unwrap();
+        log_clone.borrow_mut().push(format!("failed {}", error.event));
+    });
+    emitter.try_on(&AppEvent::Click, |_args| Err("boom"));
+
+    emitter.emit(&AppEvent::Click, args![1]);
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "added Added",
+            "added Click",
+            "added Failed",
+            "added Click",
+            "click 1",
+            "failed Click",
+        ]
+    );
+    assert_eq!(names.borrow().last().map(String::as_str), Some("Click"));
+}